@@ -0,0 +1,99 @@
+//! Demonstrates a table backed by a custom `TableModel`.
+
+extern crate iui;
+use iui::controls::{
+    Table, TableEditability, TableModel, TableModelHandle, TableTextColumn, TableValue,
+    TableValueType, WeakTableModelHandle,
+};
+use iui::prelude::*;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// A to-do list. Each row is a task, which can be marked done or bumped in progress.
+struct Tasks {
+    ui: UI,
+    tasks: RefCell<Vec<(String, bool, i32)>>,
+    handle: RefCell<Option<WeakTableModelHandle>>,
+}
+
+impl TableModel for Tasks {
+    fn num_columns(&self) -> i32 {
+        4
+    }
+
+    fn column_type(&self, column: i32) -> TableValueType {
+        match column {
+            0 | 3 => TableValueType::String,
+            _ => TableValueType::Int,
+        }
+    }
+
+    fn num_rows(&self) -> i32 {
+        self.tasks.borrow().len() as i32
+    }
+
+    fn cell_value(&self, row: i32, column: i32) -> TableValue {
+        let tasks = self.tasks.borrow();
+        let (ref name, done, progress) = tasks[row as usize];
+        match column {
+            0 => TableValue::String(name.clone()),
+            1 => TableValue::Int(done as i32),
+            2 => TableValue::Int(progress),
+            _ => TableValue::String("Work on it".into()),
+        }
+    }
+
+    fn set_cell_value(&self, row: i32, column: i32, value: Option<TableValue>) {
+        {
+            let mut tasks = self.tasks.borrow_mut();
+            let task = &mut tasks[row as usize];
+            match (column, value) {
+                (0, Some(TableValue::String(name))) => task.0 = name,
+                (1, Some(TableValue::Int(done))) => task.1 = done != 0,
+                (3, None) => task.2 = (task.2 + 10).min(100),
+                _ => {}
+            }
+        }
+        // Redraw the row, outside of the borrow, as the table reads the new values at once.
+        let handle = self
+            .handle
+            .borrow()
+            .as_ref()
+            .and_then(|handle| handle.upgrade());
+        if let Some(handle) = handle {
+            handle.row_changed(&self.ui, row);
+        }
+    }
+}
+
+fn main() {
+    let ui = UI::init().expect("Couldn't initialize UI library");
+    let mut win = Window::new(&ui, "Table Example", 400, 300, WindowType::NoMenubar);
+
+    let tasks = Rc::new(Tasks {
+        ui: ui.clone(),
+        tasks: RefCell::new(vec![
+            ("Write the table example".into(), true, 100),
+            ("Document the table API".into(), false, 40),
+            ("Ship it".into(), false, 0),
+        ]),
+        handle: RefCell::new(None),
+    });
+    let model = TableModelHandle::new(&ui, tasks.clone());
+    // The model only holds a weak handle to itself, so that it can be freed.
+    *tasks.handle.borrow_mut() = Some(model.downgrade());
+
+    let mut table = Table::new(&ui, &model);
+    table.append_text_column(
+        &ui,
+        "Task",
+        TableTextColumn::new(0).editable(TableEditability::Always),
+    );
+    table.append_checkbox_column(&ui, "Done", 1, TableEditability::Always);
+    table.append_progress_bar_column(&ui, "Progress", 2);
+    table.append_button_column(&ui, "", 3, TableEditability::Always);
+
+    win.set_child(&ui, table);
+    win.show(&ui);
+    ui.main();
+}
@@ -1,8 +1,28 @@
+//! Ownership of the closures passed to the UI library as callbacks.
+//!
+//! Callbacks are boxed and handed to the library as `*mut c_void` data pointers. The boxes are
+//! owned by a per-thread registry, which drops them once the library can no longer call them:
+//! when a control's callback is replaced, when the control is destroyed, when a one-shot
+//! callback has run, or when the UI is torn down.
+//!
+//! A callback may replace or destroy its own owner while it is running, so callbacks released
+//! that way are retired rather than dropped immediately, and are only dropped once control has
+//! returned to the event loop.
+
+use ffi_tools;
+use std::cell::RefCell;
+use std::collections::HashMap;
 use std::mem;
 use std::os::raw::c_void;
+use std::ptr;
+use ui_sys;
 
 /// Transmutes a raw mutable pointer into a mutable reference.
+///
+/// Callbacks recover their closures through this as they are called by the UI library, so it
+/// also checks that they are called on the thread which initialized the library.
 pub unsafe fn from_void_ptr<'ptr, F>(ptr: *mut c_void) -> &'ptr mut F {
+    ffi_tools::assert_main_thread();
     mem::transmute(ptr)
 }
 
@@ -12,6 +32,155 @@ pub fn to_heap_ptr<F>(item: F) -> *mut c_void {
     Box::into_raw(Box::new(item)) as *mut c_void
 }
 
+/// Drops a value previously placed on the heap with `to_heap_ptr`.
+///
+/// # Unsafety
+/// The pointer must have been produced by `to_heap_ptr::<F>`, and must not be used again.
+pub unsafe fn drop_heap_ptr<F>(ptr: *mut c_void) {
+    drop(Box::from_raw(ptr as *mut F))
+}
+
+/// A value placed on the heap with `to_heap_ptr`, which is dropped along with this.
+struct HeapCallback {
+    ptr: *mut c_void,
+    drop: unsafe fn(*mut c_void),
+}
+
+impl HeapCallback {
+    fn new<F>(callback: F) -> HeapCallback {
+        HeapCallback {
+            ptr: to_heap_ptr(callback),
+            drop: drop_heap_ptr::<F>,
+        }
+    }
+}
+
+impl Drop for HeapCallback {
+    fn drop(&mut self) {
+        unsafe { (self.drop)(self.ptr) }
+    }
+}
+
+#[derive(Default)]
+struct Registry {
+    /// Callbacks registered for an event on an owner, keyed by the owner's pointer and the
+    /// name of the event. Owners are usually controls; UI-wide callbacks use a null owner.
+    owned: HashMap<(usize, &'static str), HeapCallback>,
+    /// Callbacks which release themselves once they have run for the last time, keyed by
+    /// their data pointer.
+    transient: HashMap<usize, HeapCallback>,
+    /// Callbacks which have been released but may still be running.
+    retired: Vec<HeapCallback>,
+}
+
+thread_local! {
+    static REGISTRY: RefCell<Registry> = RefCell::new(Registry::default())
+}
+
+/// Retire the given callbacks, to be dropped once control returns to the event loop.
+fn retire(callbacks: Vec<HeapCallback>) {
+    if callbacks.is_empty() {
+        return;
+    }
+    let first = REGISTRY.with(|registry| {
+        let retired = &mut registry.borrow_mut().retired;
+        let first = retired.is_empty();
+        retired.extend(callbacks);
+        first
+    });
+    if first && ffi_tools::is_initialized() {
+        unsafe { ui_sys::uiQueueMain(Some(drop_retired), ptr::null_mut()) }
+    }
+}
+
+extern "C" fn drop_retired(_data: *mut c_void) {
+    // Take the callbacks out before dropping them, as their destructors may use the registry.
+    let retired =
+        REGISTRY.with(|registry| mem::replace(&mut registry.borrow_mut().retired, vec![]));
+    drop(retired);
+}
+
+/// Register the callback for the given event on the given owner, replacing any callback
+/// previously registered for it, and return the data pointer to pass to the UI library.
+pub fn register_callback<F>(owner: *mut c_void, event: &'static str, callback: F) -> *mut c_void {
+    ffi_tools::assert_main_thread();
+    let callback = HeapCallback::new(callback);
+    let ptr = callback.ptr;
+    let replaced = REGISTRY.with(|registry| {
+        registry
+            .borrow_mut()
+            .owned
+            .insert((owner as usize, event), callback)
+    });
+    retire(replaced.into_iter().collect());
+    ptr
+}
+
+/// Register a callback which will release itself with `release_transient`, and return the
+/// data pointer to pass to the UI library.
+pub fn register_transient<F>(callback: F) -> *mut c_void {
+    ffi_tools::assert_main_thread();
+    let callback = HeapCallback::new(callback);
+    let ptr = callback.ptr;
+    REGISTRY.with(|registry| {
+        registry
+            .borrow_mut()
+            .transient
+            .insert(ptr as usize, callback)
+    });
+    ptr
+}
+
+/// Make a callback registered with `register_transient` the callback for the given event on
+/// the given owner, for callbacks which must be created before their owner.
+pub fn adopt_transient(ptr: *mut c_void, owner: *mut c_void, event: &'static str) {
+    let replaced = REGISTRY.with(|registry| {
+        let mut registry = registry.borrow_mut();
+        let callback = registry
+            .transient
+            .remove(&(ptr as usize))
+            .expect("adopted callback was not registered as transient");
+        registry.owned.insert((owner as usize, event), callback)
+    });
+    retire(replaced.into_iter().collect());
+}
+
+/// Drop a callback registered with `register_transient`.
+///
+/// # Unsafety
+/// The callback must not be running, and the UI library must not call it again.
+pub unsafe fn release_transient(ptr: *mut c_void) {
+    let callback =
+        REGISTRY.with(|registry| registry.borrow_mut().transient.remove(&(ptr as usize)));
+    drop(callback);
+}
+
+/// Release every callback registered on the given owner, such as when it is destroyed.
+pub fn release_callbacks_of(owner: *mut c_void) {
+    let released = REGISTRY.with(|registry| {
+        let owned = &mut registry.borrow_mut().owned;
+        let keys: Vec<_> = owned
+            .keys()
+            .filter(|&&(key, _)| key == owner as usize)
+            .cloned()
+            .collect();
+        keys.into_iter()
+            .filter_map(|key| owned.remove(&key))
+            .collect()
+    });
+    retire(released);
+}
+
+/// Drop every registered callback.
+///
+/// # Unsafety
+/// The UI library must never call any registered callback again, as after it is uninitialized.
+pub unsafe fn release_all() {
+    let registry =
+        REGISTRY.with(|registry| mem::replace(&mut *registry.borrow_mut(), Registry::default()));
+    drop(registry);
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -30,4 +199,47 @@ mod tests {
         assert_eq!(*boxed, expected);
         mem::forget(actual);
     }
+
+    #[test]
+    fn heap_ptr_dropping() {
+        use std::rc::Rc;
+        let value = Rc::new(());
+        let ptr = to_heap_ptr(value.clone());
+        assert_eq!(Rc::strong_count(&value), 2);
+        unsafe { drop_heap_ptr::<Rc<()>>(ptr) };
+        assert_eq!(Rc::strong_count(&value), 1);
+    }
+
+    #[test]
+    fn replaced_and_released_callbacks_are_dropped() {
+        use std::rc::Rc;
+        let owner = 0x10 as *mut c_void;
+        let value = Rc::new(());
+        register_callback(owner, "on_clicked", value.clone());
+        register_callback(owner, "on_clicked", value.clone());
+        register_callback(owner, "on_changed", value.clone());
+        assert_eq!(Rc::strong_count(&value), 4);
+
+        // The replaced callback is retired until control returns to the event loop.
+        drop_retired(ptr::null_mut());
+        assert_eq!(Rc::strong_count(&value), 3);
+
+        release_callbacks_of(owner);
+        drop_retired(ptr::null_mut());
+        assert_eq!(Rc::strong_count(&value), 1);
+    }
+
+    #[test]
+    fn transient_callbacks_are_dropped() {
+        use std::rc::Rc;
+        let value = Rc::new(());
+        let ptr = register_transient(value.clone());
+        let adopted = register_transient(value.clone());
+        adopt_transient(adopted, 0x20 as *mut c_void, "handler");
+        unsafe { release_transient(ptr) };
+        assert_eq!(Rc::strong_count(&value), 2);
+
+        unsafe { release_all() };
+        assert_eq!(Rc::strong_count(&value), 1);
+    }
 }
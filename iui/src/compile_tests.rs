@@ -24,6 +24,7 @@
 //!     for i in &v {
 //!         println!("{}", i);
 //!     }
+//!     true
 //! });
 //!
 //! ev.quit();
@@ -66,3 +67,18 @@
 //! ui.quit();
 //! ev.next_tick(&ui);
 //! ```
+//!
+//! Here we try to queue a closure holding a reference-counted control through a `UIHandle`,
+//! which would send the control to whichever thread runs the closure.
+//!
+//! ```compile_fail
+//! # use iui::prelude::*;
+//! # use iui::controls::Label;
+//! use std::rc::Rc;
+//!
+//! let ui = UI::init().unwrap();
+//! let label = Rc::new(Label::new(&ui, "Label"));
+//! let handle = ui.handle();
+//!
+//! handle.queue_main(move |_| drop(label)).unwrap();
+//! ```
@@ -1,13 +1,18 @@
 //! Provides a way to allocate an area in the window for custom drawing.
 
+use callback_helpers::{adopt_transient, register_transient};
 use controls::Control;
 use draw;
+use error::UIError;
+use std::cell::Cell;
 use std::mem;
-use std::os::raw::c_int;
+use std::os::raw::{c_int, c_void};
+use std::ptr;
 use ui::UI;
 pub use ui_sys::uiExtKey as ExtKey;
 use ui_sys::{
     self, uiArea, uiAreaDrawParams, uiAreaHandler, uiAreaKeyEvent, uiAreaMouseEvent, uiControl,
+    uiWindowResizeEdge,
 };
 
 pub trait AreaHandler {
@@ -20,6 +25,27 @@ pub trait AreaHandler {
     }
 }
 
+thread_local! {
+    /// The area whose handler is being given a mouse button press, if any. The UI library
+    /// only allows window moves and resizes to begin from such a press.
+    static PRESSED_AREA: Cell<*mut uiArea> = Cell::new(ptr::null_mut())
+}
+
+/// Run `f` while the given area is recorded as handling a mouse button press, if `pressed`,
+/// or as not handling one otherwise.
+fn with_pressed_area<R, F: FnOnce() -> R>(ui_area: *mut uiArea, pressed: bool, f: F) -> R {
+    let current = if pressed { ui_area } else { ptr::null_mut() };
+    let previous = PRESSED_AREA.with(|area| area.replace(current));
+    let result = f();
+    PRESSED_AREA.with(|area| area.set(previous));
+    result
+}
+
+/// Returns `true` if the given area is handling a mouse button press.
+fn is_pressed_area(ui_area: *mut uiArea) -> bool {
+    !ui_area.is_null() && PRESSED_AREA.with(|area| area.get() == ui_area)
+}
+
 #[repr(C)]
 struct RustAreaHandler {
     ui_area_handler: uiAreaHandler,
@@ -27,8 +53,10 @@ struct RustAreaHandler {
 }
 
 impl RustAreaHandler {
-    fn new(_ctx: &UI, trait_object: Box<dyn AreaHandler>) -> Box<RustAreaHandler> {
-        return Box::new(RustAreaHandler {
+    /// Place a new handler on the heap, owned by the callback registry until it is adopted by
+    /// its `Area`.
+    fn new(_ctx: &UI, trait_object: Box<dyn AreaHandler>) -> *mut c_void {
+        return register_transient(RustAreaHandler {
             ui_area_handler: uiAreaHandler {
                 Draw: Some(draw),
                 MouseEvent: Some(mouse_event),
@@ -52,7 +80,6 @@ impl RustAreaHandler {
                     .trait_object
                     .draw(&area, &area_draw_params);
                 mem::forget(area_draw_params);
-                mem::forget(area);
             }
         }
 
@@ -65,11 +92,12 @@ impl RustAreaHandler {
                 let area = Area::from_ui_area(ui_area);
                 let area_mouse_event =
                     AreaMouseEvent::from_ui_area_mouse_event(&*ui_area_mouse_event);
-                (*(ui_area_handler as *mut RustAreaHandler))
-                    .trait_object
-                    .mouse_event(&area, &area_mouse_event);
+                with_pressed_area(ui_area, area_mouse_event.down != 0, || {
+                    (*(ui_area_handler as *mut RustAreaHandler))
+                        .trait_object
+                        .mouse_event(&area, &area_mouse_event)
+                });
                 mem::forget(area_mouse_event);
-                mem::forget(area);
             }
         }
 
@@ -83,7 +111,6 @@ impl RustAreaHandler {
                 (*(ui_area_handler as *mut RustAreaHandler))
                     .trait_object
                     .mouse_crossed(&area, left != 0);
-                mem::forget(area);
             }
         }
 
@@ -93,7 +120,6 @@ impl RustAreaHandler {
                 (*(ui_area_handler as *mut RustAreaHandler))
                     .trait_object
                     .drag_broken(&area);
-                mem::forget(area);
             }
         }
 
@@ -109,7 +135,6 @@ impl RustAreaHandler {
                     .trait_object
                     .key_event(&area, &area_key_event);
                 mem::forget(area_key_event);
-                mem::forget(area);
                 result as c_int
             }
         }
@@ -150,11 +175,9 @@ impl Area {
     /// Creates a new non-scrolling area.
     pub fn new(ctx: &UI, area_handler: Box<dyn AreaHandler>) -> Area {
         unsafe {
-            let mut rust_area_handler = RustAreaHandler::new(ctx, area_handler);
-            let area = Area::from_raw(ui_sys::uiNewArea(
-                &mut *rust_area_handler as *mut RustAreaHandler as *mut uiAreaHandler,
-            ));
-            mem::forget(rust_area_handler);
+            let rust_area_handler = RustAreaHandler::new(ctx, area_handler);
+            let area = Area::from_raw(ui_sys::uiNewArea(rust_area_handler as *mut uiAreaHandler));
+            adopt_transient(rust_area_handler, area.uiArea as *mut c_void, "handler");
             area
         }
     }
@@ -167,19 +190,19 @@ impl Area {
         height: i64,
     ) -> Area {
         unsafe {
-            let mut rust_area_handler = RustAreaHandler::new(ctx, area_handler);
+            let rust_area_handler = RustAreaHandler::new(ctx, area_handler);
             let area = Area::from_raw(ui_sys::uiNewScrollingArea(
-                &mut *rust_area_handler as *mut RustAreaHandler as *mut uiAreaHandler,
+                rust_area_handler as *mut uiAreaHandler,
                 width as i32,
                 height as i32,
             ));
-            mem::forget(rust_area_handler);
+            adopt_transient(rust_area_handler, area.uiArea as *mut c_void, "handler");
             area
         }
     }
 
     pub unsafe fn from_ui_area(ui_area: *mut uiArea) -> Area {
-        Area { uiArea: ui_area }
+        Area::from_raw(ui_area)
     }
 
     /// Sets the size of the area in points.
@@ -188,13 +211,13 @@ impl Area {
     /// If called on a non-scrolling `Area`, this function's behavior is undefined.
     pub unsafe fn set_size(&self, _ctx: &UI, width: u64, height: u64) {
         // TODO: Check if the area is scrolling?
-        ui_sys::uiAreaSetSize(self.uiArea, width as i32, height as i32);
+        ui_sys::uiAreaSetSize(self.ptr(), width as i32, height as i32);
     }
 
     /// Queues the entire `Area` to be redrawn. This function returns immediately;
     /// the `Area` is redrawn when the UI thread is next non-busy.
     pub fn queue_redraw_all(&self, _ctx: &UI) {
-        unsafe { ui_sys::uiAreaQueueRedrawAll(self.uiArea) }
+        unsafe { ui_sys::uiAreaQueueRedrawAll(self.ptr()) }
     }
 
     /// Scrolls the Area to show the given rectangle. This behavior is somewhat
@@ -205,7 +228,74 @@ impl Area {
     /// If called on a non-scrolling `Area`, this function's behavior is undefined.
     pub unsafe fn scroll_to(&self, _ctx: &UI, x: f64, y: f64, width: f64, height: f64) {
         // TODO: Make some way to check whether the given area is scrolling or not.
-        ui_sys::uiAreaScrollTo(self.uiArea, x, y, width, height);
+        ui_sys::uiAreaScrollTo(self.ptr(), x, y, width, height);
+    }
+
+    /// Lets the user move the window containing this `Area` by dragging with the mouse, as
+    /// if they had pressed the mouse button on its title bar. This is intended for windows
+    /// with a custom title bar, such as borderless windows.
+    ///
+    /// This can only be done from `AreaHandler::mouse_event`, in response to a mouse button
+    /// being pressed; at any other time, this returns a
+    /// [`NoMouseDownError`](../enum.UIError.html#variant.NoMouseDownError) instead. The
+    /// move ends when the button is released.
+    pub fn begin_user_window_move(&self, _ctx: &UI) -> Result<(), UIError> {
+        if !is_pressed_area(self.ptr()) {
+            return Err(UIError::NoMouseDownError());
+        }
+        unsafe { ui_sys::uiAreaBeginUserWindowMove(self.ptr()) }
+        Ok(())
+    }
+
+    /// Lets the user resize the window containing this `Area` from the given edge by
+    /// dragging with the mouse, as if they had pressed the mouse button on that edge of its
+    /// border. This is intended for windows with custom borders, such as borderless windows.
+    ///
+    /// This can only be done from `AreaHandler::mouse_event`, in response to a mouse button
+    /// being pressed; at any other time, this returns a
+    /// [`NoMouseDownError`](../enum.UIError.html#variant.NoMouseDownError) instead. The
+    /// resize ends when the button is released.
+    pub fn begin_user_window_resize(
+        &self,
+        _ctx: &UI,
+        edge: WindowResizeEdge,
+    ) -> Result<(), UIError> {
+        if !is_pressed_area(self.ptr()) {
+            return Err(UIError::NoMouseDownError());
+        }
+        unsafe {
+            ui_sys::uiAreaBeginUserWindowResize(self.ptr(), edge.into_ui_window_resize_edge())
+        }
+        Ok(())
+    }
+}
+
+/// The edge or corner of a window being resized with `Area::begin_user_window_resize`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum WindowResizeEdge {
+    Left,
+    Top,
+    Right,
+    Bottom,
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+impl WindowResizeEdge {
+    fn into_ui_window_resize_edge(self) -> uiWindowResizeEdge {
+        use self::WindowResizeEdge::*;
+        return match self {
+            Left => ui_sys::uiWindowResizeEdgeLeft,
+            Top => ui_sys::uiWindowResizeEdgeTop,
+            Right => ui_sys::uiWindowResizeEdgeRight,
+            Bottom => ui_sys::uiWindowResizeEdgeBottom,
+            TopLeft => ui_sys::uiWindowResizeEdgeTopLeft,
+            TopRight => ui_sys::uiWindowResizeEdgeTopRight,
+            BottomLeft => ui_sys::uiWindowResizeEdgeBottomLeft,
+            BottomRight => ui_sys::uiWindowResizeEdgeBottomRight,
+        } as uiWindowResizeEdge;
     }
 }
 
@@ -320,3 +410,24 @@ impl AreaKeyEvent {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn presses_are_tracked_per_area() {
+        let first = 0x10 as *mut uiArea;
+        let second = 0x20 as *mut uiArea;
+        assert!(!is_pressed_area(first));
+        with_pressed_area(first, true, || {
+            assert!(is_pressed_area(first));
+            assert!(!is_pressed_area(second));
+            // A nested event without a press, such as one for another area, hides the press
+            // until it returns.
+            with_pressed_area(second, false, || assert!(!is_pressed_area(first)));
+            assert!(is_pressed_area(first));
+        });
+        assert!(!is_pressed_area(first));
+    }
+}
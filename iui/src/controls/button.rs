@@ -1,5 +1,5 @@
 use super::Control;
-use callback_helpers::{from_void_ptr, to_heap_ptr};
+use callback_helpers::{from_void_ptr, register_callback};
 use std::ffi::{CStr, CString};
 use std::mem;
 use std::os::raw::c_void;
@@ -24,7 +24,7 @@ impl Button {
     /// Get a copy of the existing text on the button.
     pub fn text(&self, _ctx: &UI) -> String {
         unsafe {
-            CStr::from_ptr(ui_sys::uiButtonText(self.uiButton))
+            CStr::from_ptr(ui_sys::uiButtonText(self.ptr()))
                 .to_string_lossy()
                 .into_owned()
         }
@@ -32,14 +32,14 @@ impl Button {
 
     /// Get a reference to the existing text on the button.
     pub fn text_ref(&self, _ctx: &UI) -> &CStr {
-        unsafe { CStr::from_ptr(ui_sys::uiButtonText(self.uiButton)) }
+        unsafe { CStr::from_ptr(ui_sys::uiButtonText(self.ptr())) }
     }
 
     /// Set the text on the button.
     pub fn set_text(&mut self, _ctx: &UI, text: &str) {
         unsafe {
             let c_string = CString::new(text.as_bytes().to_vec()).unwrap();
-            ui_sys::uiButtonSetText(self.uiButton, c_string.as_ptr())
+            ui_sys::uiButtonSetText(self.ptr(), c_string.as_ptr())
         }
     }
 
@@ -52,13 +52,17 @@ impl Button {
         where
             G: FnMut(&mut Button),
         {
-            let mut button = Button { uiButton: button };
+            let mut button = unsafe { Button::from_raw(button) };
             unsafe {
                 from_void_ptr::<G>(data)(&mut button);
             }
         }
         unsafe {
-            ui_sys::uiButtonOnClicked(self.uiButton, Some(c_callback::<F>), to_heap_ptr(callback));
+            ui_sys::uiButtonOnClicked(
+                self.ptr(),
+                Some(c_callback::<F>),
+                register_callback(self.ptr() as *mut c_void, "on_clicked", callback),
+            );
         }
     }
 }
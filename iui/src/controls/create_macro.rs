@@ -16,12 +16,12 @@ macro_rules! define_control {
         $(#[$attr])*
         pub struct $rust_type {
             $sys_type: *mut $sys_type,
+            alive: $crate::controls::ownership::Liveness,
         }
 
         impl Drop for $rust_type {
             fn drop(&mut self) {
-                // For now this does nothing, but in the future, when `libui` supports proper
-                // memory management, this will likely need to twiddle reference counts.
+                $crate::controls::ownership::release(self.$sys_type as *mut uiControl, &self.alive)
             }
         }
 
@@ -29,17 +29,36 @@ macro_rules! define_control {
             fn clone(&self) -> $rust_type {
                 $rust_type {
                     $sys_type: self.$sys_type,
+                    alive: $crate::controls::ownership::duplicate(
+                        self.$sys_type as *mut uiControl,
+                        &self.alive,
+                    ),
                 }
             }
         }
 
         impl Into<Control> for $rust_type {
             fn into(self) -> Control {
-                unsafe {
-                    let control = Control::from_ui_control(self.$sys_type as *mut uiControl);
-                    mem::forget(self);
-                    control
-                }
+                assert!(!self.is_destroyed(), "attempted to use a destroyed control");
+                // The handle is moved into the `Control`, rather than released and acquired.
+                let control = Control {
+                    ui_control: self.$sys_type as *mut uiControl,
+                    // Moved out of `self`, which is then forgotten rather than dropped.
+                    alive: unsafe { ::std::ptr::read(&self.alive) },
+                };
+                mem::forget(self);
+                control
+            }
+        }
+
+        impl $crate::controls::ConcreteControl for $rust_type {
+            const TYPE_SIGNATURE: u32 =
+                <$sys_type as $crate::controls::signature::TypeSignature>::TYPE_SIGNATURE;
+            const AMBIGUOUS: bool =
+                <$sys_type as $crate::controls::signature::TypeSignature>::SHARED;
+
+            unsafe fn from_ui_control_unchecked(ui_control: *mut uiControl) -> $rust_type {
+                $rust_type::from_raw(ui_control as *mut $sys_type)
             }
         }
 
@@ -76,15 +95,26 @@ macro_rules! define_control {
             #[allow(unused)]
             pub unsafe fn from_raw($sys_type: *mut $sys_type) -> $rust_type {
                 $rust_type {
-                    $sys_type: $sys_type
+                    $sys_type: $sys_type,
+                    alive: $crate::controls::ownership::acquire($sys_type as *mut uiControl),
                 }
             }
 
             /// Return the underlying pointer for this control.
+            ///
+            /// # Panics
+            /// Panics if the control has been destroyed.
             #[allow(non_snake_case)]
             pub fn ptr(&self) -> *mut $sys_type {
+                assert!(!self.is_destroyed(), "attempted to use a destroyed control");
                 self.$sys_type
             }
+
+            /// Returns `true` if the control has been destroyed, either directly or along with
+            /// a container it was in. A destroyed control can no longer be used.
+            pub fn is_destroyed(&self) -> bool {
+                $crate::controls::ownership::is_destroyed(&self.alive)
+            }
         }
     }
 }
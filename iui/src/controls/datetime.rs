@@ -0,0 +1,348 @@
+//! Controls for picking dates and times, and the calendar type they work with.
+
+use super::Control;
+use callback_helpers::{from_void_ptr, register_callback};
+use std::mem;
+use std::os::raw::c_void;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use ui::UI;
+use ui_sys::{self, tm, uiControl, uiDateTimePicker};
+
+/// A date and time of day in the local time zone, as shown by the date and time pickers.
+///
+/// Values order chronologically.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct DateTime {
+    /// The year, such as `2019`.
+    pub year: i32,
+    /// The month, from `1` to `12`.
+    pub month: u32,
+    /// The day of the month, from `1` to `31`.
+    pub day: u32,
+    /// The hour, from `0` to `23`.
+    pub hour: u32,
+    /// The minute, from `0` to `59`.
+    pub minute: u32,
+    /// The second, from `0` to `59`, or `60` during a leap second.
+    pub second: u32,
+}
+
+impl DateTime {
+    /// Create a new date and time.
+    pub fn new(year: i32, month: u32, day: u32, hour: u32, minute: u32, second: u32) -> DateTime {
+        DateTime {
+            year,
+            month,
+            day,
+            hour,
+            minute,
+            second,
+        }
+    }
+
+    fn from_tm(time: &tm) -> DateTime {
+        DateTime {
+            year: time.tm_year + 1900,
+            month: (time.tm_mon + 1) as u32,
+            day: time.tm_mday as u32,
+            hour: time.tm_hour as u32,
+            minute: time.tm_min as u32,
+            second: time.tm_sec as u32,
+        }
+    }
+
+    fn to_tm(&self) -> tm {
+        let mut time: tm = unsafe { mem::zeroed() };
+        time.tm_year = self.year - 1900;
+        time.tm_mon = self.month as i32 - 1;
+        time.tm_mday = self.day as i32;
+        time.tm_hour = self.hour as i32;
+        time.tm_min = self.minute as i32;
+        time.tm_sec = self.second as i32;
+        // Let the C library work out whether daylight saving time applies.
+        time.tm_isdst = -1;
+        time
+    }
+
+    /// Converts a point in time to the local date and time, dropping fractions of a second,
+    /// or returns `None` if it cannot be represented on this platform.
+    pub fn from_system_time(time: SystemTime) -> Option<DateTime> {
+        let seconds = match time.duration_since(UNIX_EPOCH) {
+            Ok(after) => after.as_secs() as i64,
+            Err(before) => {
+                let before = before.duration();
+                -(before.as_secs() as i64) - (before.subsec_nanos() > 0) as i64
+            }
+        };
+        local::from_unix_seconds(seconds).map(|time| DateTime::from_tm(&time))
+    }
+
+    /// Converts the local date and time to a point in time, or returns `None` if it cannot
+    /// be represented on this platform.
+    pub fn to_system_time(&self) -> Option<SystemTime> {
+        let seconds = local::to_unix_seconds(&self.to_tm())?;
+        if seconds >= 0 {
+            Some(UNIX_EPOCH + Duration::from_secs(seconds as u64))
+        } else {
+            Some(UNIX_EPOCH - Duration::from_secs(seconds.wrapping_neg() as u64))
+        }
+    }
+}
+
+/// Returns `true` if both times have the same date and time of day.
+fn same_time(a: &tm, b: &tm) -> bool {
+    DateTime::from_tm(a) == DateTime::from_tm(b)
+}
+
+/// Conversions between Unix timestamps and the local calendar, through the C library.
+#[cfg(unix)]
+mod local {
+    use super::same_time;
+    use libc;
+    use std::mem;
+    use ui_sys::tm;
+
+    pub fn from_unix_seconds(seconds: i64) -> Option<tm> {
+        let seconds = seconds as libc::time_t;
+        unsafe {
+            let mut local: libc::tm = mem::zeroed();
+            if libc::localtime_r(&seconds, &mut local).is_null() {
+                return None;
+            }
+            let mut time: tm = mem::zeroed();
+            time.tm_sec = local.tm_sec;
+            time.tm_min = local.tm_min;
+            time.tm_hour = local.tm_hour;
+            time.tm_mday = local.tm_mday;
+            time.tm_mon = local.tm_mon;
+            time.tm_year = local.tm_year;
+            time.tm_wday = local.tm_wday;
+            time.tm_yday = local.tm_yday;
+            time.tm_isdst = local.tm_isdst;
+            Some(time)
+        }
+    }
+
+    pub fn to_unix_seconds(time: &tm) -> Option<i64> {
+        unsafe {
+            let mut local: libc::tm = mem::zeroed();
+            local.tm_sec = time.tm_sec;
+            local.tm_min = time.tm_min;
+            local.tm_hour = time.tm_hour;
+            local.tm_mday = time.tm_mday;
+            local.tm_mon = time.tm_mon;
+            local.tm_year = time.tm_year;
+            local.tm_isdst = time.tm_isdst;
+            let seconds = libc::mktime(&mut local) as i64;
+            // -1 is both the error value and a valid time, one second before the epoch. It is
+            // only valid if that second is the (normalized) time which was asked for.
+            if seconds == -1 {
+                let mut normalized: tm = *time;
+                normalized.tm_sec = local.tm_sec;
+                normalized.tm_min = local.tm_min;
+                normalized.tm_hour = local.tm_hour;
+                normalized.tm_mday = local.tm_mday;
+                normalized.tm_mon = local.tm_mon;
+                normalized.tm_year = local.tm_year;
+                match from_unix_seconds(-1) {
+                    Some(ref before_epoch) if same_time(before_epoch, &normalized) => {}
+                    _ => return None,
+                }
+            }
+            Some(seconds)
+        }
+    }
+}
+
+/// Conversions between Unix timestamps and the local calendar, through the C runtime.
+#[cfg(windows)]
+mod local {
+    use super::same_time;
+    use std::mem;
+    use std::os::raw::c_int;
+    use ui_sys::tm;
+
+    extern "C" {
+        fn _localtime64_s(result: *mut tm, time: *const i64) -> c_int;
+        fn _mktime64(time: *mut tm) -> i64;
+    }
+
+    pub fn from_unix_seconds(seconds: i64) -> Option<tm> {
+        unsafe {
+            let mut time: tm = mem::zeroed();
+            match _localtime64_s(&mut time, &seconds) {
+                0 => Some(time),
+                _ => None,
+            }
+        }
+    }
+
+    pub fn to_unix_seconds(time: &tm) -> Option<i64> {
+        let mut normalized = *time;
+        let seconds = unsafe { _mktime64(&mut normalized) };
+        // -1 is both the error value and a valid time, one second before the epoch. It is
+        // only valid if that second is the (normalized) time which was asked for.
+        if seconds == -1 {
+            match from_unix_seconds(-1) {
+                Some(ref before_epoch) if same_time(before_epoch, &normalized) => {}
+                _ => return None,
+            }
+        }
+        Some(seconds)
+    }
+}
+
+define_control! {
+    /// Allows the user to pick both a date and a time of day.
+    rust_type: DateTimePicker,
+    sys_type: uiDateTimePicker
+}
+
+define_control! {
+    /// Allows the user to pick a date. The time of day of its value is unspecified.
+    rust_type: DatePicker,
+    sys_type: uiDateTimePicker
+}
+
+define_control! {
+    /// Allows the user to pick a time of day. The date of its value is unspecified.
+    rust_type: TimePicker,
+    sys_type: uiDateTimePicker
+}
+
+impl DateTimePicker {
+    /// Create a new date and time picker, showing the current date and time.
+    pub fn new(_ctx: &UI) -> DateTimePicker {
+        unsafe { DateTimePicker::from_raw(ui_sys::uiNewDateTimePicker()) }
+    }
+}
+
+impl DatePicker {
+    /// Create a new date picker, showing the current date.
+    pub fn new(_ctx: &UI) -> DatePicker {
+        unsafe { DatePicker::from_raw(ui_sys::uiNewDatePicker()) }
+    }
+}
+
+impl TimePicker {
+    /// Create a new time picker, showing the current time.
+    pub fn new(_ctx: &UI) -> TimePicker {
+        unsafe { TimePicker::from_raw(ui_sys::uiNewTimePicker()) }
+    }
+}
+
+fn value(picker: *mut uiDateTimePicker) -> DateTime {
+    unsafe {
+        let mut time: tm = mem::zeroed();
+        ui_sys::uiDateTimePickerTime(picker, &mut time);
+        DateTime::from_tm(&time)
+    }
+}
+
+fn set_value(picker: *mut uiDateTimePicker, value: DateTime) {
+    let time = value.to_tm();
+    unsafe { ui_sys::uiDateTimePickerSetTime(picker, &time) }
+}
+
+fn on_changed<F: FnMut(DateTime) + 'static>(picker: *mut uiDateTimePicker, callback: F) {
+    extern "C" fn c_callback<G>(picker: *mut uiDateTimePicker, data: *mut c_void)
+    where
+        G: FnMut(DateTime),
+    {
+        let value = value(picker);
+        unsafe { from_void_ptr::<G>(data)(value) }
+    }
+
+    unsafe {
+        ui_sys::uiDateTimePickerOnChanged(
+            picker,
+            Some(c_callback::<F>),
+            register_callback(picker as *mut c_void, "on_changed", callback),
+        );
+    }
+}
+
+impl DateTimePicker {
+    /// Get the date and time shown by the picker.
+    pub fn value(&self, _ctx: &UI) -> DateTime {
+        value(self.ptr())
+    }
+
+    /// Set the date and time shown by the picker.
+    pub fn set_value(&mut self, _ctx: &UI, value: DateTime) {
+        set_value(self.ptr(), value)
+    }
+
+    /// Registers a callback for when the user changes the date or time.
+    pub fn on_changed<'ctx, F: FnMut(DateTime) + 'static>(&mut self, _ctx: &'ctx UI, callback: F) {
+        on_changed(self.ptr(), callback)
+    }
+}
+
+impl DatePicker {
+    /// Get the date shown by the picker.
+    pub fn value(&self, _ctx: &UI) -> DateTime {
+        value(self.ptr())
+    }
+
+    /// Set the date shown by the picker. The time of day is ignored.
+    pub fn set_value(&mut self, _ctx: &UI, value: DateTime) {
+        set_value(self.ptr(), value)
+    }
+
+    /// Registers a callback for when the user changes the date.
+    pub fn on_changed<'ctx, F: FnMut(DateTime) + 'static>(&mut self, _ctx: &'ctx UI, callback: F) {
+        on_changed(self.ptr(), callback)
+    }
+}
+
+impl TimePicker {
+    /// Get the time of day shown by the picker.
+    pub fn value(&self, _ctx: &UI) -> DateTime {
+        value(self.ptr())
+    }
+
+    /// Set the time of day shown by the picker. The date is ignored.
+    pub fn set_value(&mut self, _ctx: &UI, value: DateTime) {
+        set_value(self.ptr(), value)
+    }
+
+    /// Registers a callback for when the user changes the time of day.
+    pub fn on_changed<'ctx, F: FnMut(DateTime) + 'static>(&mut self, _ctx: &'ctx UI, callback: F) {
+        on_changed(self.ptr(), callback)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tm_roundtripping() {
+        let value = DateTime::new(2019, 12, 31, 23, 59, 58);
+        let time = value.to_tm();
+        assert_eq!((time.tm_year, time.tm_mon, time.tm_mday), (119, 11, 31));
+        assert_eq!(DateTime::from_tm(&time), value);
+    }
+
+    #[test]
+    fn system_time_roundtripping() {
+        let time = UNIX_EPOCH + Duration::from_secs(1_000_000_000);
+        let value = DateTime::from_system_time(time).unwrap();
+        assert_eq!(value.year, 2001);
+        assert_eq!(value.to_system_time(), Some(time));
+    }
+
+    #[test]
+    fn the_second_before_the_epoch_is_valid() {
+        let time = UNIX_EPOCH - Duration::from_secs(1);
+        let value = DateTime::from_system_time(time).unwrap();
+        assert_eq!(value.to_system_time(), Some(time));
+    }
+
+    #[test]
+    fn ordering_is_chronological() {
+        assert!(DateTime::new(2018, 12, 31, 23, 0, 0) < DateTime::new(2019, 1, 1, 0, 0, 0));
+        assert!(DateTime::new(2019, 1, 1, 0, 0, 1) > DateTime::new(2019, 1, 1, 0, 0, 0));
+    }
+}
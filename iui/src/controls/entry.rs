@@ -4,16 +4,18 @@
 //! `\r\n` for display are added and removed by the controls.
 
 use super::Control;
-use callback_helpers::{from_void_ptr, to_heap_ptr};
+use callback_helpers::{from_void_ptr, register_callback};
+use draw::text::FontDescriptor;
+use draw::Color;
 use std::ffi::{CStr, CString};
 use std::i32;
 use std::mem;
-use std::os::raw::c_void;
+use std::os::raw::{c_int, c_void};
 use str_tools::{from_toolkit_string, to_toolkit_string};
 use ui::UI;
 use ui_sys::{
-    self, uiCheckbox, uiCombobox, uiControl, uiEntry, uiMultilineEntry, uiRadioButtons, uiSlider,
-    uiSpinbox,
+    self, uiCheckbox, uiColorButton, uiCombobox, uiControl, uiEditableCombobox, uiEntry,
+    uiFontButton, uiFontDescriptor, uiMultilineEntry, uiRadioButtons, uiSlider, uiSpinbox,
 };
 
 pub trait NumericEntry {
@@ -61,11 +63,11 @@ impl Slider {
 
 impl NumericEntry for Spinbox {
     fn value(&self, _ctx: &UI) -> i32 {
-        unsafe { ui_sys::uiSpinboxValue(self.uiSpinbox) }
+        unsafe { ui_sys::uiSpinboxValue(self.ptr()) }
     }
 
     fn set_value(&mut self, _ctx: &UI, value: i32) {
-        unsafe { ui_sys::uiSpinboxSetValue(self.uiSpinbox, value) }
+        unsafe { ui_sys::uiSpinboxSetValue(self.ptr(), value) }
     }
 
     fn on_changed<'ctx, F>(&mut self, _ctx: &'ctx UI, callback: F)
@@ -84,9 +86,9 @@ impl NumericEntry for Spinbox {
 
         unsafe {
             ui_sys::uiSpinboxOnChanged(
-                self.uiSpinbox,
+                self.ptr(),
                 Some(c_callback::<F>),
-                to_heap_ptr(callback),
+                register_callback(self.ptr() as *mut c_void, "on_changed", callback),
             );
         }
     }
@@ -94,11 +96,11 @@ impl NumericEntry for Spinbox {
 
 impl NumericEntry for Slider {
     fn value(&self, _ctx: &UI) -> i32 {
-        unsafe { ui_sys::uiSliderValue(self.uiSlider) }
+        unsafe { ui_sys::uiSliderValue(self.ptr()) }
     }
 
     fn set_value(&mut self, _ctx: &UI, value: i32) {
-        unsafe { ui_sys::uiSliderSetValue(self.uiSlider, value) }
+        unsafe { ui_sys::uiSliderSetValue(self.ptr(), value) }
     }
 
     fn on_changed<'ctx, F>(&mut self, _ctx: &'ctx UI, callback: F)
@@ -116,7 +118,11 @@ impl NumericEntry for Slider {
         }
 
         unsafe {
-            ui_sys::uiSliderOnChanged(self.uiSlider, Some(c_callback::<F>), to_heap_ptr(callback));
+            ui_sys::uiSliderOnChanged(
+                self.ptr(),
+                Some(c_callback::<F>),
+                register_callback(self.ptr() as *mut c_void, "on_changed", callback),
+            );
         }
     }
 }
@@ -133,6 +139,12 @@ define_control! {
     sys_type: uiEntry
 }
 
+define_control! {
+    /// Single-line editable text buffer, styled for entering search terms.
+    rust_type: SearchEntry,
+    sys_type: uiEntry
+}
+
 define_control! {
     /// Multi-line editable text buffer.
     rust_type: MultilineEntry,
@@ -151,20 +163,97 @@ impl PasswordEntry {
     }
 }
 
+impl SearchEntry {
+    pub fn new(_ctx: &UI) -> SearchEntry {
+        unsafe { SearchEntry::from_raw(ui_sys::uiNewSearchEntry()) }
+    }
+}
+
 impl MultilineEntry {
+    /// Create a new multi-line entry, which wraps lines too long to fit its width.
     pub fn new(_ctx: &UI) -> MultilineEntry {
         unsafe { MultilineEntry::from_raw(ui_sys::uiNewMultilineEntry()) }
     }
+
+    /// Create a new multi-line entry, which scrolls horizontally to show long lines instead
+    /// of wrapping them.
+    pub fn new_non_wrapping(_ctx: &UI) -> MultilineEntry {
+        unsafe { MultilineEntry::from_raw(ui_sys::uiNewNonWrappingMultilineEntry()) }
+    }
+
+    /// Add text to the end of the entry, without reading back its current contents.
+    pub fn append(&mut self, _ctx: &UI, text: &str) {
+        let cstring = to_toolkit_string(text);
+        unsafe { ui_sys::uiMultilineEntryAppend(self.ptr(), cstring.as_ptr()) }
+    }
+
+    /// Determine whether the user is prevented from editing the entry.
+    pub fn read_only(&self, _ctx: &UI) -> bool {
+        unsafe { ui_sys::uiMultilineEntryReadOnly(self.ptr()) != 0 }
+    }
+
+    /// Set whether the user is prevented from editing the entry. The text can still be
+    /// changed programmatically.
+    pub fn set_read_only(&mut self, _ctx: &UI, read_only: bool) {
+        unsafe { ui_sys::uiMultilineEntrySetReadOnly(self.ptr(), read_only as c_int) }
+    }
+}
+
+fn read_only(entry: *mut uiEntry) -> bool {
+    unsafe { ui_sys::uiEntryReadOnly(entry) != 0 }
+}
+
+fn set_read_only(entry: *mut uiEntry, read_only: bool) {
+    unsafe { ui_sys::uiEntrySetReadOnly(entry, read_only as c_int) }
+}
+
+impl Entry {
+    /// Determine whether the user is prevented from editing the entry.
+    pub fn read_only(&self, _ctx: &UI) -> bool {
+        read_only(self.ptr())
+    }
+
+    /// Set whether the user is prevented from editing the entry. The text can still be
+    /// changed programmatically.
+    pub fn set_read_only(&mut self, _ctx: &UI, read_only: bool) {
+        set_read_only(self.ptr(), read_only)
+    }
+}
+
+impl PasswordEntry {
+    /// Determine whether the user is prevented from editing the entry.
+    pub fn read_only(&self, _ctx: &UI) -> bool {
+        read_only(self.ptr())
+    }
+
+    /// Set whether the user is prevented from editing the entry. The text can still be
+    /// changed programmatically.
+    pub fn set_read_only(&mut self, _ctx: &UI, read_only: bool) {
+        set_read_only(self.ptr(), read_only)
+    }
+}
+
+impl SearchEntry {
+    /// Determine whether the user is prevented from editing the entry.
+    pub fn read_only(&self, _ctx: &UI) -> bool {
+        read_only(self.ptr())
+    }
+
+    /// Set whether the user is prevented from editing the entry. The text can still be
+    /// changed programmatically.
+    pub fn set_read_only(&mut self, _ctx: &UI, read_only: bool) {
+        set_read_only(self.ptr(), read_only)
+    }
 }
 
 impl TextEntry for Entry {
     fn value(&self, _ctx: &UI) -> String {
-        unsafe { from_toolkit_string(ui_sys::uiEntryText(self.uiEntry)) }
+        unsafe { from_toolkit_string(ui_sys::uiEntryText(self.ptr())) }
     }
 
     fn set_value(&mut self, _ctx: &UI, value: &str) {
         let cstring = to_toolkit_string(value);
-        unsafe { ui_sys::uiEntrySetText(self.uiEntry, cstring.as_ptr()) }
+        unsafe { ui_sys::uiEntrySetText(self.ptr(), cstring.as_ptr()) }
     }
 
     fn on_changed<'ctx, F>(&mut self, _ctx: &'ctx UI, callback: F)
@@ -182,7 +271,43 @@ impl TextEntry for Entry {
         }
 
         unsafe {
-            ui_sys::uiEntryOnChanged(self.uiEntry, Some(c_callback::<F>), to_heap_ptr(callback));
+            ui_sys::uiEntryOnChanged(
+                self.ptr(),
+                Some(c_callback::<F>),
+                register_callback(self.ptr() as *mut c_void, "on_changed", callback),
+            );
+        }
+    }
+}
+
+impl TextEntry for SearchEntry {
+    fn value(&self, _ctx: &UI) -> String {
+        unsafe { from_toolkit_string(ui_sys::uiEntryText(self.ptr())) }
+    }
+
+    fn set_value(&mut self, _ctx: &UI, value: &str) {
+        let cstring = to_toolkit_string(value);
+        unsafe { ui_sys::uiEntrySetText(self.ptr(), cstring.as_ptr()) }
+    }
+
+    fn on_changed<'ctx, F>(&mut self, _ctx: &'ctx UI, callback: F)
+    where
+        F: FnMut(String) + 'static,
+    {
+        extern "C" fn c_callback<G>(entry: *mut uiEntry, data: *mut c_void)
+        where
+            G: FnMut(String),
+        {
+            let string = unsafe { from_toolkit_string(ui_sys::uiEntryText(entry)) };
+            unsafe { from_void_ptr::<G>(data)(string) }
+        }
+
+        unsafe {
+            ui_sys::uiEntryOnChanged(
+                self.ptr(),
+                Some(c_callback::<F>),
+                register_callback(self.ptr() as *mut c_void, "on_changed", callback),
+            );
         }
     }
 }
@@ -190,45 +315,45 @@ impl TextEntry for Entry {
 impl TextEntry for PasswordEntry {
     fn value(&self, _ctx: &UI) -> String {
         unsafe {
-            CStr::from_ptr(ui_sys::uiEntryText(self.uiEntry))
+            CStr::from_ptr(ui_sys::uiEntryText(self.ptr()))
                 .to_string_lossy()
                 .into_owned()
         }
     }
     fn set_value(&mut self, _ctx: &UI, value: &str) {
         let cstring = CString::new(value.as_bytes().to_vec()).unwrap();
-        unsafe { ui_sys::uiEntrySetText(self.uiEntry, cstring.as_ptr()) }
+        unsafe { ui_sys::uiEntrySetText(self.ptr(), cstring.as_ptr()) }
     }
 
     fn on_changed<'ctx, F: FnMut(String) + 'static>(&mut self, _ctx: &'ctx UI, callback: F) {
-        unsafe {
-            let mut data: Box<Box<dyn FnMut(String)>> = Box::new(Box::new(callback));
-            ui_sys::uiEntryOnChanged(
-                self.uiEntry,
-                Some(c_callback),
-                &mut *data as *mut Box<dyn FnMut(String)> as *mut c_void,
-            );
-            mem::forget(data);
-        }
-
-        extern "C" fn c_callback(entry: *mut uiEntry, data: *mut c_void) {
+        extern "C" fn c_callback<G>(entry: *mut uiEntry, data: *mut c_void)
+        where
+            G: FnMut(String),
+        {
             unsafe {
                 let string = from_toolkit_string(ui_sys::uiEntryText(entry));
-                mem::transmute::<*mut c_void, &mut Box<dyn FnMut(String)>>(data)(string);
-                mem::forget(entry);
+                from_void_ptr::<G>(data)(string);
             }
         }
+
+        unsafe {
+            ui_sys::uiEntryOnChanged(
+                self.ptr(),
+                Some(c_callback::<F>),
+                register_callback(self.ptr() as *mut c_void, "on_changed", callback),
+            );
+        }
     }
 }
 
 impl TextEntry for MultilineEntry {
     fn value(&self, _ctx: &UI) -> String {
-        unsafe { from_toolkit_string(ui_sys::uiMultilineEntryText(self.uiMultilineEntry)) }
+        unsafe { from_toolkit_string(ui_sys::uiMultilineEntryText(self.ptr())) }
     }
 
     fn set_value(&mut self, _ctx: &UI, value: &str) {
         let cstring = to_toolkit_string(value);
-        unsafe { ui_sys::uiMultilineEntrySetText(self.uiMultilineEntry, cstring.as_ptr()) }
+        unsafe { ui_sys::uiMultilineEntrySetText(self.ptr(), cstring.as_ptr()) }
     }
 
     fn on_changed<'ctx, F>(&mut self, _ctx: &'ctx UI, callback: F)
@@ -247,9 +372,9 @@ impl TextEntry for MultilineEntry {
 
         unsafe {
             ui_sys::uiMultilineEntryOnChanged(
-                self.uiMultilineEntry,
+                self.ptr(),
                 Some(c_callback::<F>),
-                to_heap_ptr(callback),
+                register_callback(self.ptr() as *mut c_void, "on_changed", callback),
             );
         }
     }
@@ -271,17 +396,17 @@ impl Combobox {
     pub fn append(&self, _ctx: &UI, name: &str) {
         unsafe {
             let c_string = to_toolkit_string(name);
-            ui_sys::uiComboboxAppend(self.uiCombobox, c_string.as_ptr())
+            ui_sys::uiComboboxAppend(self.ptr(), c_string.as_ptr())
         }
     }
 
     /// Returns the index of the currently selected option.
     pub fn selected(&self, _ctx: &UI) -> i32 {
-        unsafe { ui_sys::uiComboboxSelected(self.uiCombobox) }
+        unsafe { ui_sys::uiComboboxSelected(self.ptr()) }
     }
 
     pub fn set_selected(&mut self, _ctx: &UI, value: i32) {
-        unsafe { ui_sys::uiComboboxSetSelected(self.uiCombobox, value) }
+        unsafe { ui_sys::uiComboboxSetSelected(self.ptr(), value) }
     }
 
     pub fn on_selected<'ctx, F>(&mut self, _ctx: &'ctx UI, callback: F)
@@ -298,9 +423,63 @@ impl Combobox {
 
         unsafe {
             ui_sys::uiComboboxOnSelected(
-                self.uiCombobox,
+                self.ptr(),
+                Some(c_callback::<F>),
+                register_callback(self.ptr() as *mut c_void, "on_selected", callback),
+            );
+        }
+    }
+}
+
+define_control! {
+    /// Allows the user to type any text, or to select one of its options from a list shown
+    /// only when selected.
+    rust_type: EditableCombobox,
+    sys_type: uiEditableCombobox
+}
+
+impl EditableCombobox {
+    /// Create a new EditableCombobox
+    pub fn new(_ctx: &UI) -> Self {
+        unsafe { EditableCombobox::from_raw(ui_sys::uiNewEditableCombobox()) }
+    }
+
+    /// Adds a new option to the combination box.
+    pub fn append(&self, _ctx: &UI, name: &str) {
+        unsafe {
+            let c_string = to_toolkit_string(name);
+            ui_sys::uiEditableComboboxAppend(self.ptr(), c_string.as_ptr())
+        }
+    }
+}
+
+impl TextEntry for EditableCombobox {
+    fn value(&self, _ctx: &UI) -> String {
+        unsafe { from_toolkit_string(ui_sys::uiEditableComboboxText(self.ptr())) }
+    }
+
+    fn set_value(&mut self, _ctx: &UI, value: &str) {
+        let cstring = to_toolkit_string(value);
+        unsafe { ui_sys::uiEditableComboboxSetText(self.ptr(), cstring.as_ptr()) }
+    }
+
+    fn on_changed<'ctx, F>(&mut self, _ctx: &'ctx UI, callback: F)
+    where
+        F: FnMut(String) + 'static,
+    {
+        extern "C" fn c_callback<G>(combobox: *mut uiEditableCombobox, data: *mut c_void)
+        where
+            G: FnMut(String),
+        {
+            let string = unsafe { from_toolkit_string(ui_sys::uiEditableComboboxText(combobox)) };
+            unsafe { from_void_ptr::<G>(data)(string) }
+        }
+
+        unsafe {
+            ui_sys::uiEditableComboboxOnChanged(
+                self.ptr(),
                 Some(c_callback::<F>),
-                to_heap_ptr(callback),
+                register_callback(self.ptr() as *mut c_void, "on_changed", callback),
             );
         }
     }
@@ -320,11 +499,11 @@ impl Checkbox {
     }
 
     pub fn checked(&self, _ctx: &UI) -> bool {
-        unsafe { ui_sys::uiCheckboxChecked(self.uiCheckbox) != 0 }
+        unsafe { ui_sys::uiCheckboxChecked(self.ptr()) != 0 }
     }
 
     pub fn set_checked(&mut self, _ctx: &UI, checked: bool) {
-        unsafe { ui_sys::uiCheckboxSetChecked(self.uiCheckbox, checked as i32) }
+        unsafe { ui_sys::uiCheckboxSetChecked(self.ptr(), checked as i32) }
     }
 
     pub fn on_toggled<'ctx, F>(&mut self, _ctx: &'ctx UI, callback: F)
@@ -341,9 +520,9 @@ impl Checkbox {
 
         unsafe {
             ui_sys::uiCheckboxOnToggled(
-                self.uiCheckbox,
+                self.ptr(),
                 Some(c_callback::<F>),
-                to_heap_ptr(callback),
+                register_callback(self.ptr() as *mut c_void, "on_toggled", callback),
             );
         }
     }
@@ -363,36 +542,146 @@ impl RadioButtons {
     pub fn append(&self, _ctx: &UI, name: &str) {
         let c_string = CString::new(name.as_bytes().to_vec()).unwrap();
         unsafe {
-            ui_sys::uiRadioButtonsAppend(self.uiRadioButtons, c_string.as_ptr());
+            ui_sys::uiRadioButtonsAppend(self.ptr(), c_string.as_ptr());
         }
     }
 
     pub fn selected(&self, _ctx: &UI) -> i32 {
-        unsafe { ui_sys::uiRadioButtonsSelected(self.uiRadioButtons) }
+        unsafe { ui_sys::uiRadioButtonsSelected(self.ptr()) }
     }
 
     pub fn set_selected(&mut self, _ctx: &UI, idx: i32) {
         unsafe {
-            ui_sys::uiRadioButtonsSetSelected(self.uiRadioButtons, idx);
+            ui_sys::uiRadioButtonsSetSelected(self.ptr(), idx);
         }
     }
 
     pub fn on_selected<'ctx, F: FnMut(i32) + 'static>(&self, _ctx: &'ctx UI, callback: F) {
+        extern "C" fn c_callback<G>(radio_buttons: *mut uiRadioButtons, data: *mut c_void)
+        where
+            G: FnMut(i32),
+        {
+            unsafe {
+                let val = ui_sys::uiRadioButtonsSelected(radio_buttons);
+                from_void_ptr::<G>(data)(val);
+            }
+        }
+
         unsafe {
-            let mut data: Box<Box<dyn FnMut(i32)>> = Box::new(Box::new(callback));
             ui_sys::uiRadioButtonsOnSelected(
-                self.uiRadioButtons,
-                Some(c_callback),
-                &mut *data as *mut Box<dyn FnMut(i32)> as *mut c_void,
+                self.ptr(),
+                Some(c_callback::<F>),
+                register_callback(self.ptr() as *mut c_void, "on_selected", callback),
             );
-            mem::forget(data);
         }
+    }
+}
 
-        extern "C" fn c_callback(radio_buttons: *mut uiRadioButtons, data: *mut c_void) {
-            unsafe {
-                let val = ui_sys::uiRadioButtonsSelected(radio_buttons);
-                mem::transmute::<*mut c_void, &mut Box<dyn FnMut(i32)>>(data)(val);
-            }
+define_control! {
+    /// A button showing a color, which lets the user pick a new one when clicked.
+    rust_type: ColorButton,
+    sys_type: uiColorButton
+}
+
+impl ColorButton {
+    /// Create a new color button.
+    pub fn new(_ctx: &UI) -> ColorButton {
+        unsafe { ColorButton::from_raw(ui_sys::uiNewColorButton()) }
+    }
+
+    /// Get the color shown by the button.
+    pub fn color(&self, _ctx: &UI) -> Color {
+        color(self.ptr())
+    }
+
+    /// Set the color shown by the button.
+    pub fn set_color(&mut self, _ctx: &UI, color: Color) {
+        unsafe { ui_sys::uiColorButtonSetColor(self.ptr(), color.r, color.g, color.b, color.a) }
+    }
+
+    /// Registers a callback for when the user picks a new color.
+    pub fn on_changed<'ctx, F>(&mut self, _ctx: &'ctx UI, callback: F)
+    where
+        F: FnMut(Color) + 'static,
+    {
+        extern "C" fn c_callback<G>(button: *mut uiColorButton, data: *mut c_void)
+        where
+            G: FnMut(Color),
+        {
+            let val = color(button);
+            unsafe { from_void_ptr::<G>(data)(val) }
         }
+
+        unsafe {
+            ui_sys::uiColorButtonOnChanged(
+                self.ptr(),
+                Some(c_callback::<F>),
+                register_callback(self.ptr() as *mut c_void, "on_changed", callback),
+            );
+        }
+    }
+}
+
+fn color(button: *mut uiColorButton) -> Color {
+    let mut color = Color::new(0.0, 0.0, 0.0, 0.0);
+    unsafe {
+        ui_sys::uiColorButtonColor(
+            button,
+            &mut color.r,
+            &mut color.g,
+            &mut color.b,
+            &mut color.a,
+        )
+    };
+    color
+}
+
+define_control! {
+    /// A button showing a font, which lets the user pick a new one when clicked.
+    rust_type: FontButton,
+    sys_type: uiFontButton
+}
+
+impl FontButton {
+    /// Create a new font button, showing the default font.
+    pub fn new(_ctx: &UI) -> FontButton {
+        unsafe { FontButton::from_raw(ui_sys::uiNewFontButton()) }
+    }
+
+    /// Get the font shown by the button.
+    pub fn font(&self, _ctx: &UI) -> FontDescriptor {
+        font(self.ptr())
+    }
+
+    /// Registers a callback for when the user picks a new font.
+    pub fn on_changed<'ctx, F>(&mut self, _ctx: &'ctx UI, callback: F)
+    where
+        F: FnMut(FontDescriptor) + 'static,
+    {
+        extern "C" fn c_callback<G>(button: *mut uiFontButton, data: *mut c_void)
+        where
+            G: FnMut(FontDescriptor),
+        {
+            let val = font(button);
+            unsafe { from_void_ptr::<G>(data)(val) }
+        }
+
+        unsafe {
+            ui_sys::uiFontButtonOnChanged(
+                self.ptr(),
+                Some(c_callback::<F>),
+                register_callback(self.ptr() as *mut c_void, "on_changed", callback),
+            );
+        }
+    }
+}
+
+fn font(button: *mut uiFontButton) -> FontDescriptor {
+    unsafe {
+        let mut desc: uiFontDescriptor = mem::zeroed();
+        ui_sys::uiFontButtonFont(button, &mut desc);
+        let font = FontDescriptor::from_ui_font_descriptor(&desc);
+        ui_sys::uiFreeFontButtonFont(&mut desc);
+        font
     }
 }
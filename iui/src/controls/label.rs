@@ -24,7 +24,7 @@ impl Label {
     /// Get a copy of the existing text on the label.
     pub fn text(&self, _ctx: &UI) -> String {
         unsafe {
-            CStr::from_ptr(ui_sys::uiLabelText(self.uiLabel))
+            CStr::from_ptr(ui_sys::uiLabelText(self.ptr()))
                 .to_string_lossy()
                 .into_owned()
         }
@@ -32,14 +32,14 @@ impl Label {
 
     /// Get a reference to the existing text on the label.
     pub fn text_ref(&self, _ctx: &UI) -> &CStr {
-        unsafe { CStr::from_ptr(ui_sys::uiLabelText(self.uiLabel)) }
+        unsafe { CStr::from_ptr(ui_sys::uiLabelText(self.ptr())) }
     }
 
     /// Set the text on the label.
     pub fn set_text(&mut self, _ctx: &UI, text: &str) {
         unsafe {
             let c_string = CString::new(text.as_bytes().to_vec()).unwrap();
-            ui_sys::uiLabelSetText(self.uiLabel, c_string.as_ptr())
+            ui_sys::uiLabelSetText(self.ptr(), c_string.as_ptr())
         }
     }
 }
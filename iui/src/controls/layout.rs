@@ -1,10 +1,12 @@
+use super::ownership::{children_of, insert_child, remove_child, set_only_child};
 use super::Control;
 use error::UIError;
 use std::ffi::{CStr, CString};
 use std::mem;
 use std::os::raw::c_int;
+use std::vec;
 use ui::UI;
-use ui_sys::{self, uiAlign, uiAt, uiBox, uiControl, uiGrid, uiGroup, uiSeparator, uiTab};
+use ui_sys::{self, uiAlign, uiAt, uiBox, uiControl, uiForm, uiGrid, uiGroup, uiSeparator, uiTab};
 
 /// Defines the ways in which the children of boxes can be layed out.
 pub enum LayoutStrategy {
@@ -14,6 +16,36 @@ pub enum LayoutStrategy {
     Stretchy,
 }
 
+/// An iterator over the children of a container, in the order they are laid out.
+///
+/// Only children added through this crate are included. The iterator holds its own copy of
+/// the list, so the container may be changed while iterating.
+pub struct Children {
+    inner: vec::IntoIter<Control>,
+}
+
+impl Children {
+    fn of(container: *mut uiControl) -> Children {
+        Children {
+            inner: children_of(container).into_iter(),
+        }
+    }
+}
+
+impl Iterator for Children {
+    type Item = Control;
+
+    fn next(&mut self) -> Option<Control> {
+        self.inner.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl ExactSizeIterator for Children {}
+
 define_control! {
     /// Lays out its children vertically.
     rust_type: VerticalBox,
@@ -29,18 +61,14 @@ define_control! {
 impl VerticalBox {
     /// Create a new vertical box layout.
     pub fn new(_ctx: &UI) -> VerticalBox {
-        VerticalBox {
-            uiBox: unsafe { ui_sys::uiNewVerticalBox() },
-        }
+        unsafe { VerticalBox::from_raw(ui_sys::uiNewVerticalBox()) }
     }
 }
 
 impl HorizontalBox {
     /// Create a new horizontal box layout.
     pub fn new(_ctx: &UI) -> HorizontalBox {
-        HorizontalBox {
-            uiBox: unsafe { ui_sys::uiNewHorizontalBox() },
-        }
+        unsafe { HorizontalBox::from_raw(ui_sys::uiNewHorizontalBox()) }
     }
 }
 
@@ -52,7 +80,23 @@ fn append<T: Into<Control>>(b: *mut uiBox, ctx: &UI, child: T, strategy: LayoutS
     let control = child.into();
     unsafe {
         assert!(ctx.parent_of(control.clone()).is_none());
-        ui_sys::uiBoxAppend(b, control.ui_control, stretchy as c_int)
+        ui_sys::uiBoxAppend(b, control.ui_control, stretchy as c_int);
+    }
+    insert_child(b as *mut uiControl, usize::max_value(), control);
+}
+
+fn num_children(b: *mut uiBox, _ctx: &UI) -> i32 {
+    unsafe { ui_sys::uiBoxNumChildren(b) as i32 }
+}
+
+fn delete(b: *mut uiBox, _ctx: &UI, index: i32) -> Result<i32, UIError> {
+    let n = unsafe { ui_sys::uiBoxNumChildren(b) as i32 };
+    if index >= 0 && index < n {
+        unsafe { ui_sys::uiBoxDelete(b, index) };
+        remove_child(b as *mut uiControl, index as usize);
+        Ok(n - 1)
+    } else {
+        Err(UIError::BoxIndexOutOfBounds { index, n })
     }
 }
 
@@ -67,34 +111,74 @@ fn set_padded(b: *mut uiBox, padded: bool, _ctx: &UI) {
 impl VerticalBox {
     /// Add a control to the end of the box, sized by the given layout strategy.
     pub fn append<T: Into<Control>>(&mut self, _ctx: &UI, child: T, strategy: LayoutStrategy) {
-        append(self.uiBox, _ctx, child, strategy)
+        append(self.ptr(), _ctx, child, strategy)
+    }
+
+    /// Returns the number of controls in the box.
+    pub fn num_children(&self, _ctx: &UI) -> i32 {
+        num_children(self.ptr(), _ctx)
+    }
+
+    /// Remove the control at the given index in the box. The box no longer keeps the control
+    /// alive, so it is destroyed unless another handle to it is held; keep a handle, such as
+    /// one from `children`, to add it to another container.
+    ///
+    /// Returns the number of controls in the box after removing the control, or an error if
+    /// that index was out of bounds.
+    pub fn delete(&mut self, _ctx: &UI, index: i32) -> Result<i32, UIError> {
+        delete(self.ptr(), _ctx, index)
+    }
+
+    /// Iterate over the controls in the box, in order.
+    pub fn children(&self, _ctx: &UI) -> Children {
+        Children::of(self.ptr() as *mut uiControl)
     }
 
     /// Determine whenther the box provides padding around its children.
     pub fn padded(&self, _ctx: &UI) -> bool {
-        padded(self.uiBox, _ctx)
+        padded(self.ptr(), _ctx)
     }
 
     /// Set whether or not the box should provide padding around its children.
     pub fn set_padded(&mut self, _ctx: &UI, padded: bool) {
-        set_padded(self.uiBox, padded, _ctx)
+        set_padded(self.ptr(), padded, _ctx)
     }
 }
 
 impl HorizontalBox {
     /// Add a control to the end of the box, sized by the given layout strategy.
     pub fn append<T: Into<Control>>(&mut self, _ctx: &UI, child: T, strategy: LayoutStrategy) {
-        append(self.uiBox, _ctx, child, strategy)
+        append(self.ptr(), _ctx, child, strategy)
+    }
+
+    /// Returns the number of controls in the box.
+    pub fn num_children(&self, _ctx: &UI) -> i32 {
+        num_children(self.ptr(), _ctx)
+    }
+
+    /// Remove the control at the given index in the box. The box no longer keeps the control
+    /// alive, so it is destroyed unless another handle to it is held; keep a handle, such as
+    /// one from `children`, to add it to another container.
+    ///
+    /// Returns the number of controls in the box after removing the control, or an error if
+    /// that index was out of bounds.
+    pub fn delete(&mut self, _ctx: &UI, index: i32) -> Result<i32, UIError> {
+        delete(self.ptr(), _ctx, index)
+    }
+
+    /// Iterate over the controls in the box, in order.
+    pub fn children(&self, _ctx: &UI) -> Children {
+        Children::of(self.ptr() as *mut uiControl)
     }
 
     /// Determine whenther the box provides padding around its children.
     pub fn padded(&self, _ctx: &UI) -> bool {
-        padded(self.uiBox, _ctx)
+        padded(self.ptr(), _ctx)
     }
 
     /// Set whether or not the box should provide padding around its children.
     pub fn set_padded(&mut self, _ctx: &UI, padded: bool) {
-        set_padded(self.uiBox, padded, _ctx)
+        set_padded(self.ptr(), padded, _ctx)
     }
 }
 
@@ -124,7 +208,7 @@ impl Group {
     /// Get a copy of the current group title.
     pub fn title(&self, _ctx: &UI) -> String {
         unsafe {
-            CStr::from_ptr(ui_sys::uiGroupTitle(self.uiGroup))
+            CStr::from_ptr(ui_sys::uiGroupTitle(self.ptr()))
                 .to_string_lossy()
                 .into_owned()
         }
@@ -132,30 +216,32 @@ impl Group {
 
     /// Get a reference to the existing group title.
     pub fn title_ref(&self, _ctx: &UI) -> &CStr {
-        unsafe { CStr::from_ptr(ui_sys::uiGroupTitle(self.uiGroup)) }
+        unsafe { CStr::from_ptr(ui_sys::uiGroupTitle(self.ptr())) }
     }
 
     // Set the group's title.
     pub fn set_title(&mut self, _ctx: &UI, title: &str) {
         unsafe {
             let c_string = CString::new(title.as_bytes().to_vec()).unwrap();
-            ui_sys::uiGroupSetTitle(self.uiGroup, c_string.as_ptr())
+            ui_sys::uiGroupSetTitle(self.ptr(), c_string.as_ptr())
         }
     }
 
     // Set the group's child widget.
     pub fn set_child<T: Into<Control>>(&mut self, _ctx: &UI, child: T) {
-        unsafe { ui_sys::uiGroupSetChild(self.uiGroup, child.into().ui_control) }
+        let control = child.into();
+        unsafe { ui_sys::uiGroupSetChild(self.ptr(), control.ui_control) }
+        set_only_child(self.ptr() as *mut uiControl, control);
     }
 
     // Check whether or not the group draws a margin.
     pub fn margined(&self, _ctx: &UI) -> bool {
-        unsafe { ui_sys::uiGroupMargined(self.uiGroup) != 0 }
+        unsafe { ui_sys::uiGroupMargined(self.ptr()) != 0 }
     }
 
     // Set whether or not the group draws a margin.
     pub fn set_margined(&mut self, _ctx: &UI, margined: bool) {
-        unsafe { ui_sys::uiGroupSetMargined(self.uiGroup, margined as c_int) }
+        unsafe { ui_sys::uiGroupSetMargined(self.ptr(), margined as c_int) }
     }
 }
 
@@ -172,9 +258,10 @@ impl TabGroup {
         let control = control.into();
         unsafe {
             let c_string = CString::new(name.as_bytes().to_vec()).unwrap();
-            ui_sys::uiTabAppend(self.uiTab, c_string.as_ptr(), control.ui_control);
-            ui_sys::uiTabNumPages(self.uiTab) as i32
+            ui_sys::uiTabAppend(self.ptr(), c_string.as_ptr(), control.ui_control);
         }
+        insert_child(self.ptr() as *mut uiControl, usize::max_value(), control);
+        self.num_pages(_ctx)
     }
 
     /// Add the given control before the given index in the tab group, as a new tab with a given name.
@@ -187,43 +274,132 @@ impl TabGroup {
         before: i32,
         control: T,
     ) -> i32 {
+        let control = control.into();
         unsafe {
             let c_string = CString::new(name.as_bytes().to_vec()).unwrap();
-            ui_sys::uiTabInsertAt(
-                self.uiTab,
-                c_string.as_ptr(),
-                before,
-                control.into().ui_control,
-            );
-            ui_sys::uiTabNumPages(self.uiTab) as i32
+            ui_sys::uiTabInsertAt(self.ptr(), c_string.as_ptr(), before, control.ui_control);
         }
+        insert_child(
+            self.ptr() as *mut uiControl,
+            before.max(0) as usize,
+            control,
+        );
+        self.num_pages(_ctx)
     }
 
     /// Remove the control at the given index in the tab group.
     ///
     /// Returns the number of tabs in the group after removing the tab, or an error if that index was out of bounds.
     ///
-    /// NOTE: This will leak the deleted control! We have no way of actually getting it
-    /// to decrement its reference count per `libui`'s UI as of today, unless we maintain a
-    /// separate list of children ourselves…
+    /// The tab group no longer keeps the control shown by the tab alive, so it is destroyed
+    /// unless another handle to it is held; keep a handle, such as one from `children`, to add
+    /// it to another container.
     pub fn delete(&mut self, _ctx: &UI, index: i32) -> Result<i32, UIError> {
-        let n = unsafe { ui_sys::uiTabNumPages(self.uiTab) as i32 };
-        if index < n {
-            unsafe { ui_sys::uiTabDelete(self.uiTab, index) };
-            Ok(n)
+        let n = self.num_pages(_ctx);
+        if index >= 0 && index < n {
+            unsafe { ui_sys::uiTabDelete(self.ptr(), index) };
+            remove_child(self.ptr() as *mut uiControl, index as usize);
+            Ok(n - 1)
         } else {
             Err(UIError::TabGroupIndexOutOfBounds { index, n })
         }
     }
 
+    /// Returns the number of tabs in the group.
+    pub fn num_pages(&self, _ctx: &UI) -> i32 {
+        unsafe { ui_sys::uiTabNumPages(self.ptr()) as i32 }
+    }
+
+    /// Iterate over the controls shown by each tab, in order.
+    pub fn children(&self, _ctx: &UI) -> Children {
+        Children::of(self.ptr() as *mut uiControl)
+    }
+
     /// Determine whether or not the tab group provides margins around its children.
     pub fn margined(&self, _ctx: &UI, page: i32) -> bool {
-        unsafe { ui_sys::uiTabMargined(self.uiTab, page) != 0 }
+        unsafe { ui_sys::uiTabMargined(self.ptr(), page) != 0 }
     }
 
     /// Set whether or not the tab group provides margins around its children.
     pub fn set_margined(&mut self, _ctx: &UI, page: i32, margined: bool) {
-        unsafe { ui_sys::uiTabSetMargined(self.uiTab, page, margined as c_int) }
+        unsafe { ui_sys::uiTabSetMargined(self.ptr(), page, margined as c_int) }
+    }
+}
+
+define_control! {
+    /// Lays out its children in a column, each with a label to its left.
+    rust_type: Form,
+    sys_type: uiForm
+}
+
+impl Form {
+    /// Create a new, empty form.
+    pub fn new(_ctx: &UI) -> Form {
+        unsafe { Form::from_raw(ui_sys::uiNewForm()) }
+    }
+
+    /// Add a control to the end of the form, next to the given label and sized vertically by
+    /// the given layout strategy.
+    pub fn append<T: Into<Control>>(
+        &mut self,
+        ctx: &UI,
+        label: &str,
+        child: T,
+        strategy: LayoutStrategy,
+    ) {
+        let stretchy = match strategy {
+            LayoutStrategy::Compact => false,
+            LayoutStrategy::Stretchy => true,
+        };
+        let control = child.into();
+        unsafe {
+            assert!(ctx.parent_of(control.clone()).is_none());
+            let c_string = CString::new(label.as_bytes().to_vec()).unwrap();
+            ui_sys::uiFormAppend(
+                self.ptr(),
+                c_string.as_ptr(),
+                control.ui_control,
+                stretchy as c_int,
+            );
+        }
+        insert_child(self.ptr() as *mut uiControl, usize::max_value(), control);
+    }
+
+    /// Returns the number of controls in the form.
+    pub fn num_children(&self, _ctx: &UI) -> i32 {
+        unsafe { ui_sys::uiFormNumChildren(self.ptr()) as i32 }
+    }
+
+    /// Remove the control, and its label, at the given index in the form. The form no longer
+    /// keeps the control alive, so it is destroyed unless another handle to it is held; keep a
+    /// handle, such as one from `children`, to add it to another container.
+    ///
+    /// Returns the number of controls in the form after removing the control, or an error if
+    /// that index was out of bounds.
+    pub fn delete(&mut self, _ctx: &UI, index: i32) -> Result<i32, UIError> {
+        let n = unsafe { ui_sys::uiFormNumChildren(self.ptr()) as i32 };
+        if index >= 0 && index < n {
+            unsafe { ui_sys::uiFormDelete(self.ptr(), index) };
+            remove_child(self.ptr() as *mut uiControl, index as usize);
+            Ok(n - 1)
+        } else {
+            Err(UIError::FormIndexOutOfBounds { index, n })
+        }
+    }
+
+    /// Iterate over the controls in the form, in order.
+    pub fn children(&self, _ctx: &UI) -> Children {
+        Children::of(self.ptr() as *mut uiControl)
+    }
+
+    /// Determine whether the form provides padding between its rows.
+    pub fn padded(&self, _ctx: &UI) -> bool {
+        unsafe { ui_sys::uiFormPadded(self.ptr()) != 0 }
+    }
+
+    /// Set whether or not the form should provide padding between its rows.
+    pub fn set_padded(&mut self, _ctx: &UI, padded: bool) {
+        unsafe { ui_sys::uiFormSetPadded(self.ptr(), padded as c_int) }
     }
 }
 
@@ -328,7 +504,7 @@ impl LayoutGrid {
 
     /// Returns `true` if the `LayoutGrid` is padded and `false` if not.
     pub fn padded(&self, _ctx: &UI) -> bool {
-        if unsafe { ui_sys::uiGridPadded(self.uiGrid) } == 0 {
+        if unsafe { ui_sys::uiGridPadded(self.ptr()) } == 0 {
             true
         } else {
             false
@@ -340,7 +516,7 @@ impl LayoutGrid {
         let v = if padded { 1 } else { 0 };
 
         unsafe {
-            ui_sys::uiGridSetPadded(self.uiGrid, v);
+            ui_sys::uiGridSetPadded(self.ptr(), v);
         }
     }
 
@@ -363,10 +539,16 @@ impl LayoutGrid {
             GridExpand::Vertical => (0, 1),
             GridExpand::Both => (1, 1),
         };
+        let control = control.into();
+        insert_child(
+            self.ptr() as *mut uiControl,
+            usize::max_value(),
+            control.clone(),
+        );
         unsafe {
             ui_sys::uiGridAppend(
-                self.uiGrid,
-                control.into().ui_control,
+                self.ptr(),
+                control.ui_control,
                 left,
                 height,
                 xspan,
@@ -379,6 +561,11 @@ impl LayoutGrid {
         }
     }
 
+    /// Iterate over the controls in the `LayoutGrid`, in the order they were added.
+    pub fn children(&self, _ctx: &UI) -> Children {
+        Children::of(self.ptr() as *mut uiControl)
+    }
+
     /// Inserts a control in to the `LayoutGrid` relative to an existing control.
     pub fn insert_at<T: Into<Control>, U: Into<Control>>(
         &mut self,
@@ -398,10 +585,16 @@ impl LayoutGrid {
             GridExpand::Vertical => (0, 1),
             GridExpand::Both => (1, 1),
         };
+        let control = control.into();
+        insert_child(
+            self.ptr() as *mut uiControl,
+            usize::max_value(),
+            control.clone(),
+        );
         unsafe {
             ui_sys::uiGridInsertAt(
-                self.uiGrid,
-                control.into().ui_control,
+                self.ptr(),
+                control.ui_control,
                 existing.into().ui_control,
                 at.into_ui_at(),
                 xspan,
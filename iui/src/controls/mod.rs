@@ -10,6 +10,8 @@ use std::ptr;
 #[macro_use]
 mod create_macro;
 mod label;
+mod ownership;
+mod signature;
 pub use self::label::*;
 mod button;
 pub use self::button::*;
@@ -21,8 +23,27 @@ mod entry;
 pub use self::entry::*;
 mod progressbar;
 pub use self::progressbar::*;
+mod datetime;
+pub use self::datetime::*;
 mod area;
 pub use self::area::*;
+mod table;
+pub use self::table::*;
+
+/// A specific type of control, which a `Control` can be downcast to.
+///
+/// This is implemented by every control type in this crate, and cannot be implemented outside
+/// of it.
+pub trait ConcreteControl: Into<Control> {
+    #[doc(hidden)]
+    const TYPE_SIGNATURE: u32;
+
+    #[doc(hidden)]
+    const AMBIGUOUS: bool;
+
+    #[doc(hidden)]
+    unsafe fn from_ui_control_unchecked(ui_control: *mut uiControl) -> Self;
+}
 
 /// A generic UI control. Any UI control can be turned into this type.
 ///
@@ -30,12 +51,12 @@ pub use self::area::*;
 /// whose memory is owned by the UI library.
 pub struct Control {
     ui_control: *mut uiControl,
+    alive: ownership::Liveness,
 }
 
 impl Drop for Control {
     fn drop(&mut self) {
-        // For now this does nothing, but in the future, when `libui` supports proper memory
-        // management, this will likely need to twiddle reference counts.
+        ownership::release(self.ui_control, &self.alive)
     }
 }
 
@@ -43,6 +64,7 @@ impl Clone for Control {
     fn clone(&self) -> Control {
         Control {
             ui_control: self.ui_control,
+            alive: ownership::duplicate(self.ui_control, &self.alive),
         }
     }
 }
@@ -50,7 +72,10 @@ impl Clone for Control {
 impl Control {
     /// Creates a new `Control` object from an existing `*mut uiControl`.
     pub unsafe fn from_ui_control(ui_control: *mut uiControl) -> Control {
-        Control { ui_control }
+        Control {
+            ui_control,
+            alive: ownership::acquire(ui_control),
+        }
     }
 
     /// Returns the underlying `*mut uiControl`.
@@ -58,11 +83,61 @@ impl Control {
         self.ui_control
     }
 
-    /// Destroys a control. Any use of the control after this is use-after-free; therefore, this
-    /// is marked unsafe.
-    pub unsafe fn destroy(&self) {
+    /// Get a handle to this control as the given type of control, or `None` if it is of a
+    /// different type or has been destroyed.
+    ///
+    /// Types which wrap the same kind of control in the UI library cannot be told apart, so
+    /// downcasting to them always returns `None`. These are `VerticalBox`, `HorizontalBox`
+    /// and `Spacer`; `Entry`, `PasswordEntry` and `SearchEntry`; and `DateTimePicker`,
+    /// `DatePicker` and `TimePicker`.
+    ///
+    /// ```no_run
+    /// # use iui::prelude::*;
+    /// # use iui::controls::{Button, Control};
+    /// # let ui = UI::init().unwrap();
+    /// fn relabel_buttons(ui: &UI, controls: &[Control]) {
+    ///     for control in controls {
+    ///         if let Some(mut button) = control.downcast::<Button>() {
+    ///             button.set_text(ui, "Renamed");
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    pub fn downcast<T: ConcreteControl>(&self) -> Option<T> {
+        if T::AMBIGUOUS || self.is_destroyed() {
+            return None;
+        }
+        unsafe {
+            let control = &*self.ui_control;
+            if control.Signature == signature::CONTROL_SIGNATURE
+                && control.TypeSignature == T::TYPE_SIGNATURE
+            {
+                Some(T::from_ui_control_unchecked(self.ui_control))
+            } else {
+                None
+            }
+        }
+    }
+
+    /// Returns `true` if the control has been destroyed, either directly or along with a
+    /// container it was in.
+    pub fn is_destroyed(&self) -> bool {
+        ownership::is_destroyed(&self.alive)
+    }
+
+    /// Destroys a control, along with all of its children. Any remaining handles to the
+    /// control or its children can no longer be used, and will panic if they are.
+    ///
+    /// Controls without a parent are destroyed automatically once their last handle is
+    /// dropped, so this is only needed to destroy a control early.
+    ///
+    /// # Panics
+    /// Panics if the control has a parent; it must be removed from its container first.
+    pub fn destroy(&self) {
         // Don't check for initialization here since this can be run during deinitialization.
-        ui_sys::uiControlDestroy(self.ui_control)
+        if !self.is_destroyed() {
+            unsafe { ownership::destroy(self.ui_control) }
+        }
     }
 }
 
@@ -128,3 +203,37 @@ impl UI {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::mem;
+
+    #[test]
+    fn downcasting_checks_the_type_signature() {
+        let mut ui_control: uiControl = unsafe { mem::zeroed() };
+        ui_control.Signature = signature::CONTROL_SIGNATURE;
+        ui_control.TypeSignature = 0x42746F6E;
+        let ptr: *mut uiControl = &mut ui_control;
+        let control = unsafe { Control::from_ui_control(ptr) };
+
+        let button = control.downcast::<Button>().unwrap();
+        assert_eq!(button.ptr() as *mut uiControl, ptr);
+        assert!(control.downcast::<Label>().is_none());
+
+        unsafe { (*ptr).Signature = 0 };
+        assert!(control.downcast::<Button>().is_none());
+    }
+
+    #[test]
+    fn ambiguous_types_are_never_downcast_to() {
+        let mut ui_control: uiControl = unsafe { mem::zeroed() };
+        ui_control.Signature = signature::CONTROL_SIGNATURE;
+        ui_control.TypeSignature = 0x44545069;
+        let control = unsafe { Control::from_ui_control(&mut ui_control) };
+
+        assert!(control.downcast::<DatePicker>().is_none());
+        assert!(control.downcast::<TimePicker>().is_none());
+        assert!(control.downcast::<DateTimePicker>().is_none());
+    }
+}
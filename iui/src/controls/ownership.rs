@@ -0,0 +1,292 @@
+//! Bookkeeping which gives Rust handles to controls a sound ownership model.
+//!
+//! The UI library owns the memory of every control, and destroys the children of a container
+//! along with it. This module counts the live Rust handles to each control and records the
+//! children of each container, so that:
+//!
+//! * a control which has no parent is destroyed once its last Rust handle is dropped;
+//! * a container holds a handle to each of its children, keeping them alive while they are
+//!   part of the UI tree;
+//! * destroying a control marks it and every control beneath it as destroyed, so that the
+//!   remaining Rust handles to them can no longer be used.
+//!
+//! Each handle carries the `Liveness` of its control, rather than looking it up by pointer,
+//! since the UI library may reuse the address of a destroyed control for a new one. Destroyed
+//! controls are forgotten here entirely, so a new control at the same address starts afresh.
+//!
+//! Every handle passes through here when it is created, used or dropped, so these are also
+//! where handles are checked to be on the thread which initialized the library.
+
+use super::{window, Control};
+use callback_helpers;
+use ffi_tools;
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::mem;
+use std::os::raw::c_void;
+use std::rc::Rc;
+use ui_sys::{self, uiControl};
+
+/// Whether a control is still alive, shared by every Rust handle to it and cleared once it is
+/// destroyed.
+pub(crate) type Liveness = Rc<Cell<bool>>;
+
+struct Node {
+    /// The number of live Rust handles to the control.
+    handles: usize,
+    alive: Liveness,
+}
+
+thread_local! {
+    /// The controls which have live Rust handles.
+    static NODES: RefCell<HashMap<*mut uiControl, Node>> = RefCell::new(HashMap::new());
+
+    /// The children of each container, in order, as added through this crate. The UI library
+    /// does not provide a way to retrieve the children of most containers, so they are
+    /// tracked here instead.
+    static CHILDREN: RefCell<HashMap<*mut uiControl, Vec<Control>>> = RefCell::new(HashMap::new())
+}
+
+/// Record that a new Rust handle to the given live control has been created, returning the
+/// liveness for the handle to carry.
+pub(crate) fn acquire(control: *mut uiControl) -> Liveness {
+    ffi_tools::assert_main_thread();
+    NODES.with(|nodes| {
+        let mut nodes = nodes.borrow_mut();
+        let node = nodes.entry(control).or_insert_with(|| Node {
+            handles: 0,
+            alive: Rc::new(Cell::new(true)),
+        });
+        node.handles += 1;
+        node.alive.clone()
+    })
+}
+
+/// Record that another Rust handle to the same control as the given one has been created,
+/// returning the liveness for the new handle to carry. Copies of handles to destroyed
+/// controls are not counted.
+pub(crate) fn duplicate(control: *mut uiControl, alive: &Liveness) -> Liveness {
+    if alive.get() {
+        acquire(control)
+    } else {
+        alive.clone()
+    }
+}
+
+/// Record that a Rust handle to the given control has been dropped, destroying the control if
+/// that was its last handle and it has no parent.
+pub(crate) fn release(control: *mut uiControl, alive: &Liveness) {
+    ffi_tools::assert_main_thread();
+    // The handle was already forgotten when its control was destroyed, and the pointer may
+    // now belong to another control.
+    if !alive.get() {
+        return;
+    }
+    let orphaned = NODES.with(|nodes| {
+        let mut nodes = nodes.borrow_mut();
+        let last = match nodes.get_mut(&control) {
+            Some(node) => {
+                node.handles -= 1;
+                node.handles == 0
+            }
+            None => return false,
+        };
+        if last {
+            nodes.remove(&control);
+        }
+        last
+    });
+
+    // Once the library is uninitialized, every control has already been destroyed or leaked.
+    if orphaned && ffi_tools::is_initialized() {
+        unsafe {
+            // Top-level windows are owned by the library until they are destroyed.
+            if ui_sys::uiControlParent(control).is_null() && ui_sys::uiControlToplevel(control) == 0
+            {
+                destroy(control);
+            }
+        }
+    }
+}
+
+/// Returns `true` if the control of a handle carrying the given liveness has been destroyed.
+pub(crate) fn is_destroyed(alive: &Liveness) -> bool {
+    ffi_tools::assert_main_thread();
+    !alive.get()
+}
+
+/// Destroy the given live control and everything beneath it, invalidating all Rust handles to
+/// them.
+///
+/// # Panics
+/// Panics if the control has a parent, which it must be removed from first.
+pub(crate) unsafe fn destroy(control: *mut uiControl) {
+    assert!(
+        ui_sys::uiControlParent(control).is_null(),
+        "cannot destroy a control which has a parent; remove it from its container first"
+    );
+
+    let subtree = forget_subtree(control);
+    for &member in &subtree {
+        callback_helpers::release_callbacks_of(member as *mut c_void);
+    }
+
+    ui_sys::uiControlDestroy(control);
+}
+
+/// Mark the given control and everything beneath it as destroyed, and forget them, returning
+/// the controls of the subtree.
+fn forget_subtree(control: *mut uiControl) -> Vec<*mut uiControl> {
+    // Collect the subtree first, then mark it destroyed, so that the handles held for its
+    // children are dropped without destroying anything themselves.
+    let mut subtree = vec![control];
+    let mut index = 0;
+    while index < subtree.len() {
+        subtree.extend(
+            children_of(subtree[index])
+                .iter()
+                .map(Control::as_ui_control),
+        );
+        index += 1;
+    }
+    NODES.with(|nodes| {
+        let mut nodes = nodes.borrow_mut();
+        for member in &subtree {
+            if let Some(node) = nodes.remove(member) {
+                node.alive.set(false);
+            }
+        }
+    });
+    let children: Vec<Vec<Control>> = CHILDREN.with(|children| {
+        let mut children = children.borrow_mut();
+        subtree
+            .iter()
+            .filter_map(|member| children.remove(member))
+            .collect()
+    });
+    // Dropped outside of the borrows, now that the handles are marked as destroyed.
+    drop(children);
+    window::forget_window(control);
+    subtree
+}
+
+/// Record that `child` was inserted into `container` before the given index.
+pub(crate) fn insert_child(container: *mut uiControl, index: usize, child: Control) {
+    CHILDREN.with(|children| {
+        let mut children = children.borrow_mut();
+        let list = children.entry(container).or_insert_with(Vec::new);
+        let index = index.min(list.len());
+        list.insert(index, child);
+    })
+}
+
+/// Record that the child at the given index was removed from `container`.
+pub(crate) fn remove_child(container: *mut uiControl, index: usize) {
+    let removed = CHILDREN.with(|children| match children.borrow_mut().get_mut(&container) {
+        Some(list) if index < list.len() => Some(list.remove(index)),
+        _ => None,
+    });
+    // Dropped outside of the borrow, as this may destroy the child.
+    drop(removed);
+}
+
+/// Record that `child` is now the only child of `container`, replacing any previous child.
+pub(crate) fn set_only_child(container: *mut uiControl, child: Control) {
+    let replaced = CHILDREN.with(|children| {
+        mem::replace(
+            children
+                .borrow_mut()
+                .entry(container)
+                .or_insert_with(Vec::new),
+            vec![child],
+        )
+    });
+    // Dropped outside of the borrow, as this may destroy the previous child.
+    drop(replaced);
+}
+
+/// The children of `container`, in order.
+pub(crate) fn children_of(container: *mut uiControl) -> Vec<Control> {
+    CHILDREN.with(|children| {
+        children
+            .borrow()
+            .get(&container)
+            .cloned()
+            .unwrap_or_default()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn control(id: usize) -> Control {
+        unsafe { Control::from_ui_control(id as *mut uiControl) }
+    }
+
+    fn ids(container: *mut uiControl) -> Vec<usize> {
+        children_of(container)
+            .iter()
+            .map(|child| child.as_ui_control() as usize)
+            .collect()
+    }
+
+    fn handles(id: usize) -> usize {
+        NODES.with(|nodes| {
+            nodes
+                .borrow()
+                .get(&(id as *mut uiControl))
+                .map_or(0, |node| node.handles)
+        })
+    }
+
+    #[test]
+    fn children_are_tracked_in_order() {
+        let container = 0x100 as *mut uiControl;
+        insert_child(container, usize::max_value(), control(1));
+        insert_child(container, usize::max_value(), control(3));
+        insert_child(container, 1, control(2));
+        assert_eq!(ids(container), vec![1, 2, 3]);
+
+        remove_child(container, 0);
+        remove_child(container, 5);
+        assert_eq!(ids(container), vec![2, 3]);
+        assert_eq!(ids(0x200 as *mut uiControl), vec![]);
+
+        set_only_child(container, control(4));
+        assert_eq!(ids(container), vec![4]);
+    }
+
+    #[test]
+    fn handles_are_counted() {
+        let first = control(0x300);
+        let second = first.clone();
+        insert_child(0x400 as *mut uiControl, 0, first.clone());
+        assert_eq!(handles(0x300), 3);
+        drop(first);
+        drop(second);
+        assert_eq!(handles(0x300), 1);
+        remove_child(0x400 as *mut uiControl, 0);
+        assert_eq!(handles(0x300), 0);
+    }
+
+    #[test]
+    fn destroyed_addresses_start_afresh() {
+        let container = control(0x500);
+        let child = control(0x600);
+        insert_child(container.as_ui_control(), 0, child.clone());
+        forget_subtree(container.as_ui_control());
+        assert!(container.is_destroyed());
+        assert!(child.is_destroyed());
+        assert_eq!(handles(0x500), 0);
+        assert_eq!(handles(0x600), 0);
+
+        // A new control at a reused address is unaffected by the stale handles.
+        let reused = control(0x600);
+        let stale = child.clone();
+        drop(child);
+        drop(stale);
+        assert!(!reused.is_destroyed());
+        assert_eq!(handles(0x600), 1);
+    }
+}
@@ -100,12 +100,12 @@ impl ProgressBar {
             }
             ProgressBarValue::Indeterminate => -1,
         };
-        unsafe { ui_sys::uiProgressBarSetValue(self.uiProgressBar, sys_value) }
+        unsafe { ui_sys::uiProgressBarSetValue(self.ptr(), sys_value) }
     }
 
     /// Get the value of the progress bar
     pub fn value(&self, _ctx: &UI) -> ProgressBarValue {
-        let sys_value = unsafe { ui_sys::uiProgressBarValue(self.uiProgressBar) };
+        let sys_value = unsafe { ui_sys::uiProgressBarValue(self.ptr()) };
         if sys_value.is_negative() {
             assert!(
                 sys_value == -1,
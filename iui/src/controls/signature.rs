@@ -0,0 +1,69 @@
+//! The type signatures the UI library stores in each control, which identify its type.
+
+use ui_sys::{
+    uiArea, uiBox, uiButton, uiCheckbox, uiColorButton, uiCombobox, uiDateTimePicker,
+    uiEditableCombobox, uiEntry, uiFontButton, uiForm, uiGrid, uiGroup, uiLabel, uiMultilineEntry,
+    uiProgressBar, uiRadioButtons, uiSeparator, uiSlider, uiSpinbox, uiTab, uiTable, uiWindow,
+};
+
+/// The signature common to every control.
+pub(crate) const CONTROL_SIGNATURE: u32 = 0x7569436F;
+
+/// A type of control in the UI library, identified by its type signature.
+pub(crate) trait TypeSignature {
+    const TYPE_SIGNATURE: u32;
+    /// Whether more than one control type of this crate wraps this type, so that the type
+    /// signature cannot tell them apart.
+    const SHARED: bool;
+}
+
+macro_rules! type_signatures {
+    (shared: $($sys_type:ident => $signature:expr,)*) => {
+        $(
+            impl TypeSignature for $sys_type {
+                const TYPE_SIGNATURE: u32 = $signature;
+                const SHARED: bool = true;
+            }
+        )*
+    };
+    ($($sys_type:ident => $signature:expr,)*) => {
+        $(
+            impl TypeSignature for $sys_type {
+                const TYPE_SIGNATURE: u32 = $signature;
+                const SHARED: bool = false;
+            }
+        )*
+    };
+}
+
+type_signatures! {
+    uiArea => 0x41726561,
+    uiButton => 0x42746F6E,
+    uiCheckbox => 0x43686B62,
+    uiColorButton => 0x436F6C42,
+    uiCombobox => 0x436F6D62,
+    uiEditableCombobox => 0x45644362,
+    uiFontButton => 0x466F6E42,
+    uiForm => 0x466F726D,
+    uiGrid => 0x47726964,
+    uiGroup => 0x47727062,
+    uiLabel => 0x4C61626C,
+    uiMultilineEntry => 0x4D6C6E45,
+    uiProgressBar => 0x50426172,
+    uiRadioButtons => 0x5264696F,
+    uiSeparator => 0x53657061,
+    uiSlider => 0x536C6964,
+    uiSpinbox => 0x5370696E,
+    uiTab => 0x54616273,
+    uiTable => 0x5461626C,
+    uiWindow => 0x57696E64,
+}
+
+// `VerticalBox`, `HorizontalBox` and `Spacer`, `DateTimePicker`, `DatePicker` and
+// `TimePicker`, and `Entry`, `PasswordEntry` and `SearchEntry` each wrap the same type.
+type_signatures! {
+    shared:
+    uiBox => 0x426F784C,
+    uiDateTimePicker => 0x44545069,
+    uiEntry => 0x456E7472,
+}
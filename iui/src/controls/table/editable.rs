@@ -0,0 +1,335 @@
+use super::{
+    TableEditability, TableModel, TableModelHandle, TableValue, TableValueType,
+    WeakTableModelHandle,
+};
+use error::UIError;
+use std::cell::RefCell;
+use std::rc::Rc;
+use ui::UI;
+
+/// The kinds of editable cell a table column can show, which determine how edits made by
+/// the user are decoded. Progress bar columns are never editable, so they have no kind.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CellKind {
+    /// An editable text cell, backed by a `String` model column.
+    Text,
+    /// A checkbox, backed by an `Int` model column.
+    Checkbox,
+    /// A button, whose label is taken from a `String` model column.
+    Button,
+}
+
+impl CellKind {
+    fn value_type(self) -> TableValueType {
+        match self {
+            CellKind::Text | CellKind::Button => TableValueType::String,
+            CellKind::Checkbox => TableValueType::Int,
+        }
+    }
+}
+
+/// An edit made by the user to a single cell, decoded according to its `CellKind`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CellEdit {
+    /// New text for a text cell.
+    Text(String),
+    /// The new state of a checkbox.
+    Checkbox(bool),
+    /// A button was clicked.
+    ButtonClicked,
+}
+
+/// A `TableModel` whose cells can be edited, with each edit checked before it is applied.
+///
+/// Edits reach the model through an `EditableTableModel`, which decodes the raw value passed
+/// by the table into a `CellEdit` and calls `validate` before `apply`. Edits which cannot be
+/// decoded or which fail validation are never applied, so the cell keeps its old value.
+pub trait TableEditor: TableModel {
+    /// The kind of cell shown for the given model column, or `None` if the column cannot
+    /// be edited.
+    fn cell_kind(&self, column: i32) -> Option<CellKind>;
+
+    /// Check an edit before it is applied, returning the reason it was rejected, if any.
+    fn validate(&self, _row: i32, _column: i32, _edit: &CellEdit) -> Result<(), String> {
+        Ok(())
+    }
+
+    /// Store an edit which passed validation.
+    ///
+    /// The edited row is redrawn afterwards. An edit which inserts, moves or removes rows
+    /// must report those changes through the model's `TableModelHandle` itself.
+    fn apply(&self, row: i32, column: i32, edit: CellEdit);
+}
+
+struct EditState<M> {
+    model: M,
+    on_rejected: RefCell<Option<Box<dyn FnMut(UIError)>>>,
+    handle: RefCell<Option<WeakTableModelHandle>>,
+}
+
+impl<M: TableEditor> EditState<M> {
+    fn decode(
+        &self,
+        row: i32,
+        column: i32,
+        value: Option<TableValue>,
+    ) -> Result<CellEdit, UIError> {
+        let kind = match self.model.cell_kind(column) {
+            Some(kind) => kind,
+            None => return Err(UIError::TableCellNotEditable { row, column }),
+        };
+        let edit = match (kind, value) {
+            (CellKind::Text, Some(TableValue::String(text))) => CellEdit::Text(text),
+            (CellKind::Checkbox, Some(TableValue::Int(checked))) => {
+                CellEdit::Checkbox(checked != 0)
+            }
+            (CellKind::Button, None) => CellEdit::ButtonClicked,
+            _ => {
+                return Err(UIError::TableCellTypeMismatch {
+                    row,
+                    column,
+                    expected: kind.value_type(),
+                })
+            }
+        };
+        Ok(edit)
+    }
+
+    fn edit(&self, row: i32, column: i32, value: Option<TableValue>) -> Result<(), UIError> {
+        let edit = self.decode(row, column, value)?;
+        self.model
+            .validate(row, column, &edit)
+            .map_err(|reason| UIError::TableEditRejected {
+                row,
+                column,
+                reason,
+            })?;
+        self.model.apply(row, column, edit);
+        Ok(())
+    }
+
+    fn reject(&self, error: UIError) {
+        // Take the callback out while it runs, so that it may replace itself.
+        let callback = self.on_rejected.borrow_mut().take();
+        if let Some(mut callback) = callback {
+            callback(error);
+            let mut slot = self.on_rejected.borrow_mut();
+            if slot.is_none() {
+                *slot = Some(callback);
+            }
+        }
+    }
+}
+
+impl<M: TableEditor> TableModel for EditState<M> {
+    fn num_columns(&self) -> i32 {
+        self.model.num_columns()
+    }
+
+    fn column_type(&self, column: i32) -> TableValueType {
+        self.model.column_type(column)
+    }
+
+    fn num_rows(&self) -> i32 {
+        self.model.num_rows()
+    }
+
+    fn cell_value(&self, row: i32, column: i32) -> TableValue {
+        self.model.cell_value(row, column)
+    }
+
+    fn set_cell_value(&self, row: i32, column: i32, value: Option<TableValue>) {
+        if let Err(error) = self.edit(row, column, value) {
+            self.reject(error);
+        }
+        // Redraw the edited row, showing the stored value or reverting a rejected edit, unless
+        // applying the edit removed it.
+        if row < self.model.num_rows() {
+            let handle = self
+                .handle
+                .borrow()
+                .as_ref()
+                .and_then(|handle| handle.upgrade());
+            if let Some(handle) = handle {
+                handle.notify_row_changed(row);
+            }
+        }
+    }
+}
+
+/// Registers a `TableEditor` with the UI library, decoding and validating every edit made
+/// through its tables.
+///
+/// Rejected edits leave the model untouched, and are reported as a `UIError` to the
+/// callback set with `on_rejected`. Cloning an `EditableTableModel` produces another
+/// reference to the same model.
+pub struct EditableTableModel<M> {
+    state: Rc<EditState<M>>,
+    handle: TableModelHandle,
+}
+
+impl<M> Clone for EditableTableModel<M> {
+    fn clone(&self) -> EditableTableModel<M> {
+        EditableTableModel {
+            state: self.state.clone(),
+            handle: self.handle.clone(),
+        }
+    }
+}
+
+impl<M: TableEditor + 'static> EditableTableModel<M> {
+    /// Register the given model with the UI library.
+    pub fn new(ctx: &UI, model: M) -> EditableTableModel<M> {
+        let state = Rc::new(EditState {
+            model,
+            on_rejected: RefCell::new(None),
+            handle: RefCell::new(None),
+        });
+        let handle = TableModelHandle::new(ctx, state.clone());
+        *state.handle.borrow_mut() = Some(handle.downgrade());
+        EditableTableModel { state, handle }
+    }
+
+    /// The handle used to show this model in a `Table`.
+    pub fn handle(&self) -> &TableModelHandle {
+        &self.handle
+    }
+
+    /// The model edits are applied to.
+    pub fn model(&self) -> &M {
+        &self.state.model
+    }
+
+    /// Whether the cells of the given model column can be edited, for use when adding
+    /// columns to a `Table`.
+    pub fn editability(&self, column: i32) -> TableEditability {
+        match self.state.model.cell_kind(column) {
+            Some(_) => TableEditability::Always,
+            None => TableEditability::Never,
+        }
+    }
+
+    /// Registers a callback for when an edit is rejected, either because it could not be
+    /// decoded or because it failed validation.
+    pub fn on_rejected<'ctx, F: FnMut(UIError) + 'static>(&self, _ctx: &'ctx UI, callback: F) {
+        *self.state.on_rejected.borrow_mut() = Some(Box::new(callback));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    /// A single row holding a name, a checkbox and a button.
+    struct Row {
+        name: RefCell<String>,
+        checked: Cell<bool>,
+    }
+
+    impl TableModel for Row {
+        fn num_columns(&self) -> i32 {
+            3
+        }
+
+        fn column_type(&self, column: i32) -> TableValueType {
+            match column {
+                1 => TableValueType::Int,
+                _ => TableValueType::String,
+            }
+        }
+
+        fn num_rows(&self) -> i32 {
+            1
+        }
+
+        fn cell_value(&self, _row: i32, column: i32) -> TableValue {
+            match column {
+                0 => TableValue::String(self.name.borrow().clone()),
+                1 => TableValue::Int(self.checked.get() as i32),
+                _ => TableValue::String("Reset".into()),
+            }
+        }
+    }
+
+    impl TableEditor for Row {
+        fn cell_kind(&self, column: i32) -> Option<CellKind> {
+            match column {
+                0 => Some(CellKind::Text),
+                1 => Some(CellKind::Checkbox),
+                _ => None,
+            }
+        }
+
+        fn validate(&self, _row: i32, _column: i32, edit: &CellEdit) -> Result<(), String> {
+            match *edit {
+                CellEdit::Text(ref text) if text.is_empty() => Err("name is empty".into()),
+                _ => Ok(()),
+            }
+        }
+
+        fn apply(&self, _row: i32, _column: i32, edit: CellEdit) {
+            match edit {
+                CellEdit::Text(text) => *self.name.borrow_mut() = text,
+                CellEdit::Checkbox(checked) => self.checked.set(checked),
+                _ => {}
+            }
+        }
+    }
+
+    fn state() -> EditState<Row> {
+        EditState {
+            model: Row {
+                name: RefCell::new("Ada".into()),
+                checked: Cell::new(false),
+            },
+            on_rejected: RefCell::new(None),
+            handle: RefCell::new(None),
+        }
+    }
+
+    #[test]
+    fn valid_edits_are_applied() {
+        let state = state();
+        state.edit(0, 0, Some("Grace".into())).unwrap();
+        state.edit(0, 1, Some(TableValue::Int(1))).unwrap();
+        assert_eq!(*state.model.name.borrow(), "Grace");
+        assert!(state.model.checked.get());
+    }
+
+    #[test]
+    fn invalid_edits_keep_the_old_value() {
+        let state = state();
+        match state.edit(0, 0, Some("".into())) {
+            Err(UIError::TableEditRejected {
+                row: 0, column: 0, ..
+            }) => {}
+            other => panic!("unexpected result {:?}", other),
+        }
+        match state.edit(0, 1, Some("yes".into())) {
+            Err(UIError::TableCellTypeMismatch {
+                expected: TableValueType::Int,
+                ..
+            }) => {}
+            other => panic!("unexpected result {:?}", other),
+        }
+        match state.edit(0, 2, None) {
+            Err(UIError::TableCellNotEditable { row: 0, column: 2 }) => {}
+            other => panic!("unexpected result {:?}", other),
+        }
+        assert_eq!(*state.model.name.borrow(), "Ada");
+        assert!(!state.model.checked.get());
+    }
+
+    #[test]
+    fn rejections_are_reported() {
+        let state = state();
+        let rejected = Rc::new(Cell::new(0));
+        let count = rejected.clone();
+        *state.on_rejected.borrow_mut() = Some(Box::new(move |_| count.set(count.get() + 1)));
+
+        state.set_cell_value(0, 0, Some("".into()));
+        state.set_cell_value(0, 0, Some("Grace".into()));
+        assert_eq!(rejected.get(), 1);
+    }
+}
@@ -0,0 +1,380 @@
+use super::{TableModel, TableModelHandle, TableValue, TableValueType};
+use std::cell::{Cell, RefCell};
+use std::collections::{BTreeMap, HashMap};
+use std::ops::Range;
+use std::rc::Rc;
+use ui::UI;
+
+/// Supplies the rows of a `LazyTableModel` on demand.
+///
+/// Rows are fetched by index whenever a table needs one which is not in the model's cache,
+/// so a source backed by a file, database or network service never needs to hold more than
+/// a handful of rows in memory.
+pub trait RowSource {
+    /// The number of model columns. This must not change over the life of the source.
+    fn num_columns(&self) -> i32;
+    /// The type of the values in the given model column. This must not change over the
+    /// life of the source.
+    fn column_type(&self, column: i32) -> TableValueType;
+    /// The current number of rows. This is only read when the model is created and when
+    /// `LazyTableModel::refresh` is called. Tables can show at most `i32::MAX` rows, so any
+    /// rows past that are never shown.
+    fn num_rows(&self) -> usize;
+    /// Fetch every cell of the given row, in column order. Missing cells are shown empty.
+    fn row(&self, index: usize) -> Vec<TableValue>;
+}
+
+/// A `RowSource` which fetches rows with a closure.
+pub struct FnRowSource<F> {
+    column_types: Vec<TableValueType>,
+    num_rows: Cell<usize>,
+    fetch: F,
+}
+
+impl<F: Fn(usize) -> Vec<TableValue>> FnRowSource<F> {
+    /// Create a source with columns of the given types and the given number of rows, each
+    /// of which is produced by calling `fetch` with its index.
+    pub fn new(column_types: Vec<TableValueType>, num_rows: usize, fetch: F) -> FnRowSource<F> {
+        FnRowSource {
+            column_types,
+            num_rows: Cell::new(num_rows),
+            fetch,
+        }
+    }
+
+    /// Change the number of rows. Tables pick up the change on the next call to
+    /// `LazyTableModel::refresh`.
+    pub fn set_num_rows(&self, num_rows: usize) {
+        self.num_rows.set(num_rows);
+    }
+}
+
+impl<F: Fn(usize) -> Vec<TableValue>> RowSource for FnRowSource<F> {
+    fn num_columns(&self) -> i32 {
+        self.column_types.len() as i32
+    }
+
+    fn column_type(&self, column: i32) -> TableValueType {
+        self.column_types[column as usize]
+    }
+
+    fn num_rows(&self) -> usize {
+        self.num_rows.get()
+    }
+
+    fn row(&self, index: usize) -> Vec<TableValue> {
+        (self.fetch)(index)
+    }
+}
+
+/// A cache holding at most a fixed number of entries, which evicts the least recently used
+/// entry to make room for a new one.
+struct LruCache<V> {
+    capacity: usize,
+    /// The next use stamp to hand out. Stamps only increase, so the smallest one in `uses`
+    /// belongs to the least recently used entry.
+    clock: u64,
+    entries: HashMap<usize, (V, u64)>,
+    uses: BTreeMap<u64, usize>,
+}
+
+impl<V> LruCache<V> {
+    fn new(capacity: usize) -> LruCache<V> {
+        LruCache {
+            capacity,
+            clock: 0,
+            entries: HashMap::new(),
+            uses: BTreeMap::new(),
+        }
+    }
+
+    fn tick(&mut self) -> u64 {
+        self.clock += 1;
+        self.clock
+    }
+
+    fn get(&mut self, key: usize) -> Option<&V> {
+        let stamp = self.tick();
+        match self.entries.get_mut(&key) {
+            Some(entry) => {
+                self.uses.remove(&entry.1);
+                self.uses.insert(stamp, key);
+                entry.1 = stamp;
+                Some(&entry.0)
+            }
+            None => None,
+        }
+    }
+
+    fn insert(&mut self, key: usize, value: V) {
+        if self.capacity == 0 {
+            return;
+        }
+        self.remove(key);
+        while self.entries.len() >= self.capacity {
+            let oldest = *self.uses.keys().next().unwrap();
+            let evicted = self.uses.remove(&oldest).unwrap();
+            self.entries.remove(&evicted);
+        }
+        let stamp = self.tick();
+        self.uses.insert(stamp, key);
+        self.entries.insert(key, (value, stamp));
+    }
+
+    fn remove(&mut self, key: usize) {
+        if let Some((_, stamp)) = self.entries.remove(&key) {
+            self.uses.remove(&stamp);
+        }
+    }
+
+    /// Remove every entry whose key is at least `start`.
+    fn truncate(&mut self, start: usize) {
+        let keys: Vec<usize> = self
+            .entries
+            .keys()
+            .cloned()
+            .filter(|&key| key >= start)
+            .collect();
+        for key in keys {
+            self.remove(key);
+        }
+    }
+
+    fn clear(&mut self) {
+        self.entries.clear();
+        self.uses.clear();
+    }
+
+    fn len(&self) -> usize {
+        self.entries.len()
+    }
+}
+
+struct LazyState<S> {
+    source: S,
+    /// The number of rows the tables were last told about.
+    num_rows: Cell<usize>,
+    cache: RefCell<LruCache<Vec<TableValue>>>,
+}
+
+impl<S: RowSource> LazyState<S> {
+    /// The number of rows of the source which tables can show.
+    fn source_rows(&self) -> usize {
+        self.source.num_rows().min(i32::max_value() as usize)
+    }
+
+    /// The given cell of a fetched row, or an empty value if the source left it out.
+    fn cell_of(&self, cells: &[TableValue], column: i32) -> TableValue {
+        match cells.get(column as usize) {
+            Some(value) => value.clone(),
+            None => TableValue::empty(self.column_type(column)),
+        }
+    }
+}
+
+impl<S: RowSource> TableModel for LazyState<S> {
+    fn num_columns(&self) -> i32 {
+        self.source.num_columns()
+    }
+
+    fn column_type(&self, column: i32) -> TableValueType {
+        self.source.column_type(column)
+    }
+
+    fn num_rows(&self) -> i32 {
+        self.num_rows.get().min(i32::max_value() as usize) as i32
+    }
+
+    fn cell_value(&self, row: i32, column: i32) -> TableValue {
+        let row = row as usize;
+        if let Some(cells) = self.cache.borrow_mut().get(row) {
+            return self.cell_of(cells, column);
+        }
+        let cells = self.source.row(row);
+        let value = self.cell_of(&cells, column);
+        self.cache.borrow_mut().insert(row, cells);
+        value
+    }
+}
+
+/// A read-only `TableModel` which fetches rows from a `RowSource` only as tables need them,
+/// keeping the most recently used rows in a cache of bounded size.
+///
+/// This makes it possible to show millions of rows: only the rows a table actually draws
+/// are ever fetched, and memory use is limited by the cache capacity rather than the size
+/// of the data set. The capacity should comfortably exceed the number of rows visible at
+/// once, so that scrolling back and forth is served from the cache. Cloning a
+/// `LazyTableModel` produces another reference to the same model.
+///
+/// ```no_run
+/// # use iui::prelude::*;
+/// # use iui::controls::{FnRowSource, LazyTableModel, Table, TableTextColumn, TableValue,
+/// #     TableValueType};
+/// let ui = UI::init().unwrap();
+/// let source = FnRowSource::new(vec![TableValueType::String], 1_000_000, |index| {
+///     vec![TableValue::String(format!("Line {}", index + 1))]
+/// });
+/// let model = LazyTableModel::new(&ui, source, 1024);
+///
+/// let mut table = Table::new(&ui, model.handle());
+/// table.append_text_column(&ui, "Log", TableTextColumn::new(0));
+/// ```
+pub struct LazyTableModel<S> {
+    state: Rc<LazyState<S>>,
+    handle: TableModelHandle,
+}
+
+impl<S> Clone for LazyTableModel<S> {
+    fn clone(&self) -> LazyTableModel<S> {
+        LazyTableModel {
+            state: self.state.clone(),
+            handle: self.handle.clone(),
+        }
+    }
+}
+
+impl<S: RowSource + 'static> LazyTableModel<S> {
+    /// Create a model showing the rows of the given source, caching at most `capacity`
+    /// fetched rows.
+    pub fn new(ctx: &UI, source: S, capacity: usize) -> LazyTableModel<S> {
+        let state = Rc::new(LazyState {
+            num_rows: Cell::new(0),
+            source,
+            cache: RefCell::new(LruCache::new(capacity)),
+        });
+        state.num_rows.set(state.source_rows());
+        let handle = TableModelHandle::new(ctx, state.clone());
+        LazyTableModel { state, handle }
+    }
+
+    /// The handle used to show this model in a `Table`.
+    pub fn handle(&self) -> &TableModelHandle {
+        &self.handle
+    }
+
+    /// The source rows are fetched from.
+    pub fn source(&self) -> &S {
+        &self.state.source
+    }
+
+    /// The number of rows currently shown.
+    pub fn num_rows(&self) -> usize {
+        self.state.num_rows.get()
+    }
+
+    /// The number of rows currently cached.
+    pub fn num_cached_rows(&self) -> usize {
+        self.state.cache.borrow().len()
+    }
+
+    /// Pick up a change in the number of rows of the source, such as new lines appended to
+    /// a log. Rows are assumed to have been added or removed at the end; rows which remain
+    /// keep their cached values.
+    pub fn refresh(&self, _ctx: &UI) {
+        let new_rows = self.state.source_rows();
+        let mut old_rows = self.state.num_rows.get();
+        self.state
+            .cache
+            .borrow_mut()
+            .truncate(new_rows.min(old_rows));
+
+        while old_rows < new_rows {
+            self.state.num_rows.set(old_rows + 1);
+            self.handle.notify_row_inserted(old_rows as i32);
+            old_rows += 1;
+        }
+        while old_rows > new_rows {
+            old_rows -= 1;
+            self.state.num_rows.set(old_rows);
+            self.handle.notify_row_deleted(old_rows as i32);
+        }
+    }
+
+    /// Drop the cached values of the given rows, and have the tables fetch them again.
+    pub fn invalidate_rows(&self, _ctx: &UI, rows: Range<usize>) {
+        let end = rows.end.min(self.state.num_rows.get());
+        for row in rows.start..end {
+            self.state.cache.borrow_mut().remove(row);
+            self.handle.notify_row_changed(row as i32);
+        }
+    }
+
+    /// Drop every cached row. Rows are fetched again as tables next draw them.
+    pub fn clear_cache(&self, _ctx: &UI) {
+        self.state.cache.borrow_mut().clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lru_evicts_least_recently_used() {
+        let mut cache = LruCache::new(2);
+        cache.insert(1, "one");
+        cache.insert(2, "two");
+        assert_eq!(cache.get(1), Some(&"one"));
+
+        cache.insert(3, "three");
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.get(2), None);
+        assert_eq!(cache.get(1), Some(&"one"));
+        assert_eq!(cache.get(3), Some(&"three"));
+    }
+
+    #[test]
+    fn lru_reinsert_replaces_value() {
+        let mut cache = LruCache::new(2);
+        cache.insert(1, "one");
+        cache.insert(2, "two");
+        cache.insert(1, "uno");
+        cache.insert(3, "three");
+
+        assert_eq!(cache.get(1), Some(&"uno"));
+        assert_eq!(cache.get(2), None);
+        assert_eq!(cache.uses.len(), cache.len());
+    }
+
+    #[test]
+    fn lru_truncate_and_zero_capacity() {
+        let mut cache = LruCache::new(4);
+        for key in 0..4 {
+            cache.insert(key, key);
+        }
+        cache.truncate(2);
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.get(3), None);
+        assert_eq!(cache.get(1), Some(&1));
+
+        let mut empty = LruCache::new(0);
+        empty.insert(0, 0);
+        assert_eq!(empty.len(), 0);
+    }
+
+    #[test]
+    fn short_rows_and_huge_sources_are_shown_safely() {
+        let columns = vec![TableValueType::String, TableValueType::Int];
+        let source = FnRowSource::new(columns, usize::max_value(), |index| {
+            vec![TableValue::String(index.to_string())]
+        });
+        let state = LazyState {
+            num_rows: Cell::new(0),
+            source,
+            cache: RefCell::new(LruCache::new(4)),
+        };
+        state.num_rows.set(state.source_rows());
+        assert_eq!(state.num_rows(), i32::max_value());
+
+        for _ in 0..2 {
+            match state.cell_value(7, 0) {
+                TableValue::String(ref text) if text == "7" => {}
+                other => panic!("unexpected value {:?}", other),
+            }
+            match state.cell_value(7, 1) {
+                TableValue::Int(0) => {}
+                other => panic!("unexpected value {:?}", other),
+            }
+        }
+    }
+}
@@ -0,0 +1,242 @@
+//! Tables which display rows of data from a `TableModel`.
+
+use super::Control;
+use callback_helpers::register_callback;
+use std::ffi::CString;
+use std::mem;
+use std::os::raw::{c_int, c_void};
+use ui::UI;
+use ui_sys::{self, uiControl, uiTable, uiTableParams, uiTableTextColumnOptionalParams};
+
+mod editable;
+pub use self::editable::{CellEdit, CellKind, EditableTableModel, TableEditor};
+mod lazy_model;
+pub use self::lazy_model::{FnRowSource, LazyTableModel, RowSource};
+mod model;
+pub use self::model::*;
+mod tree;
+pub use self::tree::{TreeModel, TreeToggle, TreeView};
+mod value;
+pub use self::value::*;
+mod vec_model;
+pub use self::vec_model::{TableRow, VecTableModel, VecTableView};
+
+/// Determines whether the cells of a table column can be edited (or, for button columns,
+/// clicked) by the user.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TableEditability {
+    /// The cells can never be edited.
+    Never,
+    /// The cells can always be edited.
+    Always,
+    /// Each cell can be edited if the given model column, which must be of type `Int`,
+    /// holds a nonzero value in that row.
+    ByColumn(i32),
+}
+
+impl TableEditability {
+    fn into_model_column(self) -> c_int {
+        match self {
+            TableEditability::Never => ui_sys::uiTableModelColumnNeverEditable as c_int,
+            TableEditability::Always => ui_sys::uiTableModelColumnAlwaysEditable as c_int,
+            TableEditability::ByColumn(column) => column,
+        }
+    }
+}
+
+/// Describes the text shown in a text, image-and-text or checkbox-and-text column.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TableTextColumn {
+    /// The model column, of type `String`, holding the text.
+    pub model_column: i32,
+    /// Whether or not the text can be edited.
+    pub editable: TableEditability,
+    /// The model column, of type `Color`, holding the color of the text. If `None`, the
+    /// text is drawn in the default color.
+    pub color_column: Option<i32>,
+}
+
+impl TableTextColumn {
+    /// Show the text in the given model column, without editing and in the default color.
+    pub fn new(model_column: i32) -> TableTextColumn {
+        TableTextColumn {
+            model_column,
+            editable: TableEditability::Never,
+            color_column: None,
+        }
+    }
+
+    /// Set whether or not the text can be edited.
+    pub fn editable(mut self, editable: TableEditability) -> TableTextColumn {
+        self.editable = editable;
+        self
+    }
+
+    /// Draw the text in the color held by the given model column.
+    pub fn color_column(mut self, color_column: i32) -> TableTextColumn {
+        self.color_column = Some(color_column);
+        self
+    }
+
+    fn optional_params(&self) -> uiTableTextColumnOptionalParams {
+        uiTableTextColumnOptionalParams {
+            ColorModelColumn: self.color_column.unwrap_or(-1),
+        }
+    }
+}
+
+define_control! {
+    /// Displays the rows of a `TableModel` in a set of columns.
+    ///
+    /// Each column shows one or more model columns, in one of the forms libui supports: text,
+    /// images, images with text, checkboxes, checkboxes with text, progress bars and buttons.
+    rust_type: Table,
+    sys_type: uiTable
+}
+
+impl Table {
+    /// Create a new table showing the given model. The table starts out without any columns.
+    pub fn new(_ctx: &UI, model: &TableModelHandle) -> Table {
+        Table::new_with_params(model, -1)
+    }
+
+    /// Create a new table showing the given model, with the background of each row drawn in
+    /// the color held by the given model column, of type `Color`.
+    pub fn new_with_row_background(
+        _ctx: &UI,
+        model: &TableModelHandle,
+        background_color_column: i32,
+    ) -> Table {
+        Table::new_with_params(model, background_color_column)
+    }
+
+    fn new_with_params(model: &TableModelHandle, background_color_column: i32) -> Table {
+        let mut params = uiTableParams {
+            Model: model.ptr(),
+            RowBackgroundColorModelColumn: background_color_column,
+        };
+        unsafe {
+            let table = Table::from_raw(ui_sys::uiNewTable(&mut params));
+            // The table uses the model until it is destroyed, which releases this handle.
+            register_callback(table.ptr() as *mut c_void, "model", model.clone());
+            table
+        }
+    }
+
+    /// Add a column showing text.
+    pub fn append_text_column(&mut self, _ctx: &UI, name: &str, text: TableTextColumn) {
+        let c_string = CString::new(name.as_bytes().to_vec()).unwrap();
+        let mut text_params = text.optional_params();
+        unsafe {
+            ui_sys::uiTableAppendTextColumn(
+                self.ptr(),
+                c_string.as_ptr(),
+                text.model_column,
+                text.editable.into_model_column(),
+                &mut text_params,
+            )
+        }
+    }
+
+    /// Add a column showing the images in the given model column, of type `Image`.
+    pub fn append_image_column(&mut self, _ctx: &UI, name: &str, image_column: i32) {
+        let c_string = CString::new(name.as_bytes().to_vec()).unwrap();
+        unsafe { ui_sys::uiTableAppendImageColumn(self.ptr(), c_string.as_ptr(), image_column) }
+    }
+
+    /// Add a column showing an image, from a model column of type `Image`, followed by text.
+    pub fn append_image_text_column(
+        &mut self,
+        _ctx: &UI,
+        name: &str,
+        image_column: i32,
+        text: TableTextColumn,
+    ) {
+        let c_string = CString::new(name.as_bytes().to_vec()).unwrap();
+        let mut text_params = text.optional_params();
+        unsafe {
+            ui_sys::uiTableAppendImageTextColumn(
+                self.ptr(),
+                c_string.as_ptr(),
+                image_column,
+                text.model_column,
+                text.editable.into_model_column(),
+                &mut text_params,
+            )
+        }
+    }
+
+    /// Add a column showing checkboxes, checked if the given model column, of type `Int`,
+    /// is nonzero. When toggled by the user, the new state is passed to the model as
+    /// `TableValue::Int(0)` or `TableValue::Int(1)`.
+    pub fn append_checkbox_column(
+        &mut self,
+        _ctx: &UI,
+        name: &str,
+        checkbox_column: i32,
+        editable: TableEditability,
+    ) {
+        let c_string = CString::new(name.as_bytes().to_vec()).unwrap();
+        unsafe {
+            ui_sys::uiTableAppendCheckboxColumn(
+                self.ptr(),
+                c_string.as_ptr(),
+                checkbox_column,
+                editable.into_model_column(),
+            )
+        }
+    }
+
+    /// Add a column showing a checkbox, as in `append_checkbox_column`, followed by text.
+    pub fn append_checkbox_text_column(
+        &mut self,
+        _ctx: &UI,
+        name: &str,
+        checkbox_column: i32,
+        checkbox_editable: TableEditability,
+        text: TableTextColumn,
+    ) {
+        let c_string = CString::new(name.as_bytes().to_vec()).unwrap();
+        let mut text_params = text.optional_params();
+        unsafe {
+            ui_sys::uiTableAppendCheckboxTextColumn(
+                self.ptr(),
+                c_string.as_ptr(),
+                checkbox_column,
+                checkbox_editable.into_model_column(),
+                text.model_column,
+                text.editable.into_model_column(),
+                &mut text_params,
+            )
+        }
+    }
+
+    /// Add a column showing progress bars, with values taken from the given model column of
+    /// type `Int`. Values run from `0` to `100`; `-1` shows an indeterminate progress bar.
+    pub fn append_progress_bar_column(&mut self, _ctx: &UI, name: &str, progress_column: i32) {
+        let c_string = CString::new(name.as_bytes().to_vec()).unwrap();
+        unsafe {
+            ui_sys::uiTableAppendProgressBarColumn(self.ptr(), c_string.as_ptr(), progress_column)
+        }
+    }
+
+    /// Add a column showing buttons labelled with the text in the given model column, of type
+    /// `String`. Clicking a button calls `set_cell_value` on the model with a value of `None`.
+    pub fn append_button_column(
+        &mut self,
+        _ctx: &UI,
+        name: &str,
+        button_column: i32,
+        clickable: TableEditability,
+    ) {
+        let c_string = CString::new(name.as_bytes().to_vec()).unwrap();
+        unsafe {
+            ui_sys::uiTableAppendButtonColumn(
+                self.ptr(),
+                c_string.as_ptr(),
+                button_column,
+                clickable.into_model_column(),
+            )
+        }
+    }
+}
@@ -0,0 +1,300 @@
+use super::{TableValue, TableValueType};
+use ffi_tools;
+use image::Image;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::os::raw::c_int;
+use std::rc::{Rc, Weak};
+use ui::UI;
+use ui_sys::{self, uiTableModel, uiTableModelHandler, uiTableValue, uiTableValueType};
+
+/// Provides the data shown by a `Table`.
+///
+/// A model is a grid of `TableValue`s. Each model column has a fixed `TableValueType`; the
+/// columns shown by a `Table` pick which model columns they display. A model column can also
+/// be used to store per-row metadata, such as whether a cell is editable or what color its
+/// text is drawn in.
+///
+/// The model is shared between Rust and the UI library, which calls into it whenever it needs
+/// to draw a cell, so every method takes `&self`. Models which change should use interior
+/// mutability, and must tell every `TableModelHandle` they are attached to about the change
+/// with [`row_inserted`], [`row_changed`] or [`row_deleted`], outside of any borrow the model
+/// itself relies on. A model which keeps its own handle for this must hold it as a
+/// `WeakTableModelHandle`, or it will never be freed.
+///
+/// [`row_inserted`]: struct.TableModelHandle.html#method.row_inserted
+/// [`row_changed`]: struct.TableModelHandle.html#method.row_changed
+/// [`row_deleted`]: struct.TableModelHandle.html#method.row_deleted
+pub trait TableModel {
+    /// The number of model columns. This must not change over the life of the model.
+    fn num_columns(&self) -> i32;
+    /// The type of the values in the given model column. This must not change over the
+    /// life of the model.
+    fn column_type(&self, column: i32) -> TableValueType;
+    /// The current number of rows.
+    fn num_rows(&self) -> i32;
+    /// The value of the given cell, which must be of the column's type.
+    fn cell_value(&self, row: i32, column: i32) -> TableValue;
+    /// Called when the user edits a cell. `value` is `None` when a button is clicked.
+    ///
+    /// The model is responsible for telling its tables about any rows the edit changes,
+    /// moves or removes, including the edited row itself. A row which is not redrawn keeps
+    /// showing the user's edit, even if the model did not store it.
+    fn set_cell_value(&self, _row: i32, _column: i32, _value: Option<TableValue>) {}
+}
+
+#[repr(C)]
+struct RustTableModelHandler {
+    ui_table_model_handler: uiTableModelHandler,
+    model: Rc<dyn TableModel>,
+    /// The image last handed to the UI library for each cell, by row and column. The library
+    /// only borrows each image until it next asks for the cell, which replaces it here.
+    images: RefCell<HashMap<(c_int, c_int), Rc<Image>>>,
+}
+
+impl RustTableModelHandler {
+    fn new(model: Rc<dyn TableModel>) -> Box<RustTableModelHandler> {
+        return Box::new(RustTableModelHandler {
+            ui_table_model_handler: uiTableModelHandler {
+                NumColumns: Some(num_columns),
+                ColumnType: Some(column_type),
+                NumRows: Some(num_rows),
+                CellValue: Some(cell_value),
+                SetCellValue: Some(set_cell_value),
+            },
+            model,
+            images: RefCell::new(HashMap::new()),
+        });
+
+        extern "C" fn num_columns(
+            handler: *mut uiTableModelHandler,
+            _ui_table_model: *mut uiTableModel,
+        ) -> c_int {
+            unsafe { (*(handler as *mut RustTableModelHandler)).model.num_columns() }
+        }
+
+        extern "C" fn column_type(
+            handler: *mut uiTableModelHandler,
+            _ui_table_model: *mut uiTableModel,
+            column: c_int,
+        ) -> uiTableValueType {
+            unsafe {
+                (*(handler as *mut RustTableModelHandler))
+                    .model
+                    .column_type(column)
+                    .into_ui_table_value_type()
+            }
+        }
+
+        extern "C" fn num_rows(
+            handler: *mut uiTableModelHandler,
+            _ui_table_model: *mut uiTableModel,
+        ) -> c_int {
+            unsafe { (*(handler as *mut RustTableModelHandler)).model.num_rows() }
+        }
+
+        extern "C" fn cell_value(
+            handler: *mut uiTableModelHandler,
+            _ui_table_model: *mut uiTableModel,
+            row: c_int,
+            column: c_int,
+        ) -> *mut uiTableValue {
+            unsafe {
+                let handler = &*(handler as *mut RustTableModelHandler);
+                let value = handler.model.cell_value(row, column);
+                if let TableValue::Image(ref image) = value {
+                    handler
+                        .images
+                        .borrow_mut()
+                        .insert((row, column), image.clone());
+                }
+                value.as_ui_table_value()
+            }
+        }
+
+        extern "C" fn set_cell_value(
+            handler: *mut uiTableModelHandler,
+            _ui_table_model: *mut uiTableModel,
+            row: c_int,
+            column: c_int,
+            value: *const uiTableValue,
+        ) {
+            unsafe {
+                let value = if value.is_null() {
+                    None
+                } else {
+                    TableValue::from_ui_table_value(value)
+                };
+                (*(handler as *mut RustTableModelHandler))
+                    .model
+                    .set_cell_value(row, column, value);
+            }
+        }
+    }
+}
+
+/// A model registered with the UI library, which is freed along with its handler once the
+/// last handle to it is dropped.
+struct RegisteredModel {
+    ui_table_model: *mut uiTableModel,
+    handler: *mut RustTableModelHandler,
+}
+
+impl Drop for RegisteredModel {
+    fn drop(&mut self) {
+        unsafe {
+            // Once the library is uninitialized, the model can no longer be freed through it.
+            if ffi_tools::is_initialized() {
+                ui_sys::uiFreeTableModel(self.ui_table_model);
+            }
+            drop(Box::from_raw(self.handler));
+        }
+    }
+}
+
+/// A `TableModel` registered with the UI library, which can be displayed by any number of
+/// `Table`s.
+///
+/// Cloning the handle produces another reference to the same model. Every `Table` showing
+/// the model holds one of these references until it is destroyed, and the model is freed
+/// once the last of them is dropped.
+#[derive(Clone)]
+pub struct TableModelHandle {
+    model: Rc<RegisteredModel>,
+}
+
+impl TableModelHandle {
+    /// Register the given model with the UI library.
+    pub fn new<M: TableModel + 'static>(_ctx: &UI, model: Rc<M>) -> TableModelHandle {
+        ffi_tools::assert_main_thread();
+        let handler = Box::into_raw(RustTableModelHandler::new(model));
+        unsafe {
+            TableModelHandle {
+                model: Rc::new(RegisteredModel {
+                    ui_table_model: ui_sys::uiNewTableModel(handler as *mut uiTableModelHandler),
+                    handler,
+                }),
+            }
+        }
+    }
+
+    /// Create a handle to a model which is not registered with the UI library, and which
+    /// must not be shown or notified.
+    #[cfg(test)]
+    pub(crate) fn unregistered<M: TableModel + 'static>(model: Rc<M>) -> TableModelHandle {
+        TableModelHandle {
+            model: Rc::new(RegisteredModel {
+                ui_table_model: ::std::ptr::null_mut(),
+                handler: Box::into_raw(RustTableModelHandler::new(model)),
+            }),
+        }
+    }
+
+    /// Create a weak reference to the model, which does not keep it alive.
+    pub fn downgrade(&self) -> WeakTableModelHandle {
+        WeakTableModelHandle {
+            model: Rc::downgrade(&self.model),
+        }
+    }
+
+    /// Tell all tables showing this model that a row was inserted at the given index.
+    /// The model must already report the new row.
+    pub fn row_inserted(&self, _ctx: &UI, index: i32) {
+        self.notify_row_inserted(index)
+    }
+
+    /// Tell all tables showing this model that the row at the given index changed.
+    pub fn row_changed(&self, _ctx: &UI, index: i32) {
+        self.notify_row_changed(index)
+    }
+
+    /// Tell all tables showing this model that the row at the given index was deleted.
+    /// The model must already have removed the row.
+    pub fn row_deleted(&self, _ctx: &UI, index: i32) {
+        self.notify_row_deleted(index)
+    }
+
+    pub(crate) fn notify_row_inserted(&self, index: i32) {
+        ffi_tools::assert_main_thread();
+        unsafe { ui_sys::uiTableModelRowInserted(self.ptr(), index) }
+    }
+
+    pub(crate) fn notify_row_changed(&self, index: i32) {
+        ffi_tools::assert_main_thread();
+        unsafe { ui_sys::uiTableModelRowChanged(self.ptr(), index) }
+    }
+
+    pub(crate) fn notify_row_deleted(&self, index: i32) {
+        ffi_tools::assert_main_thread();
+        unsafe { ui_sys::uiTableModelRowDeleted(self.ptr(), index) }
+        // Tables no longer show the rows past the new end, so their images can be dropped.
+        let handler = unsafe { &*self.model.handler };
+        let num_rows = handler.model.num_rows();
+        handler
+            .images
+            .borrow_mut()
+            .retain(|&(row, _), _| row < num_rows);
+    }
+
+    /// Return the underlying pointer for this model.
+    pub fn ptr(&self) -> *mut uiTableModel {
+        self.model.ui_table_model
+    }
+}
+
+/// A weak reference to a `TableModelHandle`, which does not keep the model alive.
+///
+/// Models which notify their own tables of changes should keep one of these, rather than a
+/// `TableModelHandle`, since a model which holds a strong handle to itself is never freed.
+#[derive(Clone)]
+pub struct WeakTableModelHandle {
+    model: Weak<RegisteredModel>,
+}
+
+impl WeakTableModelHandle {
+    /// Get a handle to the model, or `None` if it has been freed.
+    pub fn upgrade(&self) -> Option<TableModelHandle> {
+        self.model.upgrade().map(|model| TableModelHandle { model })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Empty;
+
+    impl TableModel for Empty {
+        fn num_columns(&self) -> i32 {
+            0
+        }
+
+        fn column_type(&self, _column: i32) -> TableValueType {
+            TableValueType::Int
+        }
+
+        fn num_rows(&self) -> i32 {
+            0
+        }
+
+        fn cell_value(&self, _row: i32, _column: i32) -> TableValue {
+            TableValue::Int(0)
+        }
+    }
+
+    #[test]
+    fn models_are_freed_with_their_last_handle() {
+        let model = Rc::new(Empty);
+        let handle = TableModelHandle::unregistered(model.clone());
+        let weak = handle.downgrade();
+        let copy = handle.clone();
+
+        drop(handle);
+        assert!(weak.upgrade().is_some());
+        assert_eq!(Rc::strong_count(&model), 2);
+
+        drop(copy);
+        assert!(weak.upgrade().is_none());
+        assert_eq!(Rc::strong_count(&model), 1);
+    }
+}
@@ -0,0 +1,578 @@
+use super::{
+    Table, TableEditability, TableModel, TableModelHandle, TableValue, TableValueType,
+    WeakTableModelHandle,
+};
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::rc::Rc;
+use ui::UI;
+
+/// Provides hierarchical data shown by a `TreeView`.
+///
+/// Each node is identified by its path: the index of the node among its parent's children,
+/// preceded by the path of its parent. Top-level nodes are the children of the empty path.
+/// As with `TableModel`, every method takes `&self`, so models which change should use
+/// interior mutability and tell their `TreeView` about the change.
+pub trait TreeModel {
+    /// The number of columns each node has. This must not change over the life of the model.
+    fn num_columns(&self) -> i32;
+    /// The type of the values in the given column. This must not change over the life of
+    /// the model.
+    fn column_type(&self, column: i32) -> TableValueType;
+    /// The number of children of the node at the given path.
+    fn num_children(&self, path: &[usize]) -> usize;
+    /// The value of the given column for the node at the given path.
+    fn cell_value(&self, path: &[usize], column: i32) -> TableValue;
+    /// Called when the user edits a cell of the node at the given path. `value` is `None`
+    /// when a button is clicked.
+    fn set_cell_value(&self, _path: &[usize], _column: i32, _value: Option<TableValue>) {}
+}
+
+/// How a `TreeView` shows the control used to expand and collapse nodes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TreeToggle {
+    /// A button labelled with an arrow pointing right when collapsed, or down when expanded.
+    Button,
+    /// A checkbox, checked while the node is expanded.
+    Checkbox,
+}
+
+/// The model column holding each row's expand/collapse toggle.
+const TOGGLE_COLUMN: i32 = 0;
+/// The model column, of type `Int`, which is nonzero for rows that have children.
+const HAS_CHILDREN_COLUMN: i32 = 1;
+/// The number of model columns which come before the columns of the `TreeModel`.
+const TREE_COLUMNS_START: i32 = 2;
+/// The text added in front of the first column for each level of nesting.
+const INDENT: &str = "    ";
+
+struct TreeState<M> {
+    model: M,
+    toggle: TreeToggle,
+    /// The path of each visible node, in the order they are shown.
+    rows: RefCell<Vec<Vec<usize>>>,
+    expanded: RefCell<HashSet<Vec<usize>>>,
+    handle: RefCell<Option<WeakTableModelHandle>>,
+}
+
+impl<M: TreeModel> TreeState<M> {
+    fn new(model: M, toggle: TreeToggle) -> TreeState<M> {
+        let state = TreeState {
+            model,
+            toggle,
+            rows: RefCell::new(Vec::new()),
+            expanded: RefCell::new(HashSet::new()),
+            handle: RefCell::new(None),
+        };
+        *state.rows.borrow_mut() = state.visible_descendants(&[]);
+        state
+    }
+
+    /// The paths of every node below the given one which is shown, given the current
+    /// expansion state.
+    fn visible_descendants(&self, path: &[usize]) -> Vec<Vec<usize>> {
+        let mut descendants = Vec::new();
+        self.collect_visible(&mut path.to_vec(), &mut descendants);
+        descendants
+    }
+
+    fn collect_visible(&self, path: &mut Vec<usize>, descendants: &mut Vec<Vec<usize>>) {
+        for index in 0..self.model.num_children(path) {
+            path.push(index);
+            descendants.push(path.clone());
+            if self.expanded.borrow().contains(path) {
+                self.collect_visible(path, descendants);
+            }
+            path.pop();
+        }
+    }
+
+    fn row_of(&self, path: &[usize]) -> Option<usize> {
+        self.rows.borrow().iter().position(|row| &row[..] == path)
+    }
+
+    fn path_of(&self, row: usize) -> Option<Vec<usize>> {
+        self.rows.borrow().get(row).cloned()
+    }
+
+    fn handle(&self) -> Option<TableModelHandle> {
+        self.handle
+            .borrow()
+            .as_ref()
+            .and_then(|handle| handle.upgrade())
+    }
+
+    fn notify_inserted(&self, row: usize) {
+        if let Some(handle) = self.handle() {
+            handle.notify_row_inserted(row as i32);
+        }
+    }
+
+    fn notify_changed(&self, row: usize) {
+        if let Some(handle) = self.handle() {
+            handle.notify_row_changed(row as i32);
+        }
+    }
+
+    fn notify_deleted(&self, row: usize) {
+        if let Some(handle) = self.handle() {
+            handle.notify_row_deleted(row as i32);
+        }
+    }
+
+    /// Shows the visible descendants of the node in the given row, one row at a time.
+    fn show_descendants(&self, row: usize) {
+        let path = self.rows.borrow()[row].clone();
+        for (offset, descendant) in self.visible_descendants(&path).into_iter().enumerate() {
+            self.rows.borrow_mut().insert(row + 1 + offset, descendant);
+            self.notify_inserted(row + 1 + offset);
+        }
+    }
+
+    /// Hides every descendant of the node in the given row, one row at a time.
+    fn hide_descendants(&self, row: usize) {
+        loop {
+            let removed = {
+                let mut rows = self.rows.borrow_mut();
+                let is_descendant = rows.get(row + 1).map_or(false, |next| {
+                    next.len() > rows[row].len() && next.starts_with(&rows[row])
+                });
+                if is_descendant {
+                    rows.remove(row + 1);
+                }
+                is_descendant
+            };
+            if !removed {
+                break;
+            }
+            self.notify_deleted(row + 1);
+        }
+    }
+
+    fn expand(&self, path: &[usize]) {
+        if path.is_empty() || !self.expanded.borrow_mut().insert(path.to_vec()) {
+            return;
+        }
+        if let Some(row) = self.row_of(path) {
+            self.show_descendants(row);
+            self.notify_changed(row);
+        }
+    }
+
+    fn collapse(&self, path: &[usize]) {
+        if !self.expanded.borrow_mut().remove(path) {
+            return;
+        }
+        if let Some(row) = self.row_of(path) {
+            self.hide_descendants(row);
+            self.notify_changed(row);
+        }
+    }
+
+    fn is_expanded(&self, path: &[usize]) -> bool {
+        self.expanded.borrow().contains(path)
+    }
+
+    /// Shows the current children of the node at the given path. Whether any of its
+    /// descendants were expanded is forgotten, since they may now be different nodes.
+    fn children_changed(&self, path: &[usize]) {
+        self.expanded
+            .borrow_mut()
+            .retain(|expanded| expanded.len() <= path.len() || !expanded.starts_with(path));
+        if path.is_empty() {
+            self.refresh();
+        } else if let Some(row) = self.row_of(path) {
+            let shown = self.rows.borrow()[row + 1..]
+                .iter()
+                .take_while(|next| next.len() > path.len() && next.starts_with(path))
+                .count();
+            let descendants = if self.is_expanded(path) {
+                self.visible_descendants(path)
+            } else {
+                Vec::new()
+            };
+            self.replace_rows(row + 1, shown, descendants);
+            self.notify_changed(row);
+        }
+    }
+
+    /// Replaces `len` rows, starting at `start`, with the given paths. The rows in both are
+    /// redrawn, and only the rest are inserted or deleted, so that the tables keep their
+    /// selection and scroll position.
+    fn replace_rows(&self, start: usize, len: usize, paths: Vec<Vec<usize>>) {
+        let common = len.min(paths.len());
+        for row in (start + common..start + len).rev() {
+            self.rows.borrow_mut().remove(row);
+            self.notify_deleted(row);
+        }
+
+        let mut paths = paths.into_iter();
+        {
+            let mut rows = self.rows.borrow_mut();
+            for (row, path) in rows[start..start + common].iter_mut().zip(&mut paths) {
+                *row = path;
+            }
+        }
+        for row in start..start + common {
+            self.notify_changed(row);
+        }
+
+        for (row, path) in (start + common..).zip(paths) {
+            self.rows.borrow_mut().insert(row, path);
+            self.notify_inserted(row);
+        }
+    }
+
+    /// Shows the rows of the model as it is now.
+    fn refresh(&self) {
+        let len = self.rows.borrow().len();
+        self.replace_rows(0, len, self.visible_descendants(&[]));
+    }
+}
+
+impl<M: TreeModel> TableModel for TreeState<M> {
+    fn num_columns(&self) -> i32 {
+        TREE_COLUMNS_START + self.model.num_columns()
+    }
+
+    fn column_type(&self, column: i32) -> TableValueType {
+        match column {
+            TOGGLE_COLUMN => match self.toggle {
+                TreeToggle::Button => TableValueType::String,
+                TreeToggle::Checkbox => TableValueType::Int,
+            },
+            HAS_CHILDREN_COLUMN => TableValueType::Int,
+            _ => self.model.column_type(column - TREE_COLUMNS_START),
+        }
+    }
+
+    fn num_rows(&self) -> i32 {
+        self.rows.borrow().len() as i32
+    }
+
+    fn cell_value(&self, row: i32, column: i32) -> TableValue {
+        let path = match self.path_of(row as usize) {
+            Some(path) => path,
+            // The table asked for a row it was never told about; show it as empty rather than
+            // panicking, which would abort inside the UI library's callback.
+            None => return TableValue::empty(self.column_type(column)),
+        };
+        let has_children = self.model.num_children(&path) > 0;
+        match column {
+            TOGGLE_COLUMN => match self.toggle {
+                TreeToggle::Button if !has_children => TableValue::String(String::new()),
+                TreeToggle::Button if self.is_expanded(&path) => TableValue::String("▾".into()),
+                TreeToggle::Button => TableValue::String("▸".into()),
+                TreeToggle::Checkbox => TableValue::Int(self.is_expanded(&path) as i32),
+            },
+            HAS_CHILDREN_COLUMN => TableValue::Int(has_children as i32),
+            TREE_COLUMNS_START => match self.model.cell_value(&path, 0) {
+                TableValue::String(text) => {
+                    TableValue::String(INDENT.repeat(path.len() - 1) + &text)
+                }
+                value => value,
+            },
+            _ => self.model.cell_value(&path, column - TREE_COLUMNS_START),
+        }
+    }
+
+    fn set_cell_value(&self, row: i32, column: i32, value: Option<TableValue>) {
+        let path = match self.path_of(row as usize) {
+            Some(path) => path,
+            None => return,
+        };
+        match column {
+            TOGGLE_COLUMN => {
+                if self.is_expanded(&path) {
+                    self.collapse(&path);
+                } else if self.model.num_children(&path) > 0 {
+                    self.expand(&path);
+                }
+            }
+            HAS_CHILDREN_COLUMN => {}
+            _ => {
+                self.model
+                    .set_cell_value(&path, column - TREE_COLUMNS_START, value);
+                if let Some(row) = self.row_of(&path) {
+                    self.notify_changed(row);
+                }
+            }
+        }
+    }
+}
+
+/// Shows the nodes of a `TreeModel` as the rows of a `Table`, with each node's children
+/// listed below it while it is expanded.
+///
+/// libui has no tree control, so the tree is flattened: every visible node becomes a row,
+/// the text of the first tree column is indented by the node's depth, and a toggle column
+/// expands and collapses nodes which have children. Expanding or collapsing a node inserts
+/// or deletes only the rows below it, and nodes remember whether they were expanded while
+/// hidden by a collapsed ancestor.
+///
+/// The model columns seen by the `Table` differ from those of the `TreeModel`; use
+/// `column` to find the table model column for a tree column. Cloning a `TreeView`
+/// produces another reference to the same view.
+///
+/// ```no_run
+/// # use iui::prelude::*;
+/// # use iui::controls::{Table, TableTextColumn, TableValue, TableValueType, TreeModel,
+/// #     TreeToggle, TreeView};
+/// struct Numbers;
+///
+/// impl TreeModel for Numbers {
+///     fn num_columns(&self) -> i32 { 1 }
+///     fn column_type(&self, _column: i32) -> TableValueType { TableValueType::String }
+///     fn num_children(&self, path: &[usize]) -> usize { if path.len() < 3 { 10 } else { 0 } }
+///     fn cell_value(&self, path: &[usize], _column: i32) -> TableValue {
+///         TableValue::String(format!("{:?}", path))
+///     }
+/// }
+///
+/// let ui = UI::init().unwrap();
+/// let tree = TreeView::new(&ui, Numbers, TreeToggle::Button);
+/// let mut table = Table::new(&ui, tree.handle());
+/// tree.append_toggle_column(&ui, &mut table, "");
+/// table.append_text_column(&ui, "Path", TableTextColumn::new(tree.column(0)));
+/// ```
+pub struct TreeView<M> {
+    state: Rc<TreeState<M>>,
+    handle: TableModelHandle,
+}
+
+impl<M> Clone for TreeView<M> {
+    fn clone(&self) -> TreeView<M> {
+        TreeView {
+            state: self.state.clone(),
+            handle: self.handle.clone(),
+        }
+    }
+}
+
+impl<M: TreeModel + 'static> TreeView<M> {
+    /// Create a view of the given model, with every node collapsed.
+    pub fn new(ctx: &UI, model: M, toggle: TreeToggle) -> TreeView<M> {
+        let state = Rc::new(TreeState::new(model, toggle));
+        let handle = TableModelHandle::new(ctx, state.clone());
+        *state.handle.borrow_mut() = Some(handle.downgrade());
+        TreeView { state, handle }
+    }
+
+    /// The handle used to show this view in a `Table`.
+    pub fn handle(&self) -> &TableModelHandle {
+        &self.handle
+    }
+
+    /// The model shown by this view.
+    pub fn model(&self) -> &M {
+        &self.state.model
+    }
+
+    /// The table model column showing the given column of the tree model.
+    pub fn column(&self, tree_column: i32) -> i32 {
+        TREE_COLUMNS_START + tree_column
+    }
+
+    /// Add the column used to expand and collapse nodes to the given table, as a button or
+    /// checkbox column depending on how this view was created.
+    pub fn append_toggle_column(&self, ctx: &UI, table: &mut Table, name: &str) {
+        let editable = TableEditability::ByColumn(HAS_CHILDREN_COLUMN);
+        match self.state.toggle {
+            TreeToggle::Button => table.append_button_column(ctx, name, TOGGLE_COLUMN, editable),
+            TreeToggle::Checkbox => {
+                table.append_checkbox_column(ctx, name, TOGGLE_COLUMN, editable)
+            }
+        }
+    }
+
+    /// Expand the node at the given path, showing its children.
+    pub fn expand(&self, _ctx: &UI, path: &[usize]) {
+        self.state.expand(path)
+    }
+
+    /// Collapse the node at the given path, hiding its children.
+    pub fn collapse(&self, _ctx: &UI, path: &[usize]) {
+        self.state.collapse(path)
+    }
+
+    /// Returns `true` if the node at the given path is expanded.
+    pub fn is_expanded(&self, path: &[usize]) -> bool {
+        self.state.is_expanded(path)
+    }
+
+    /// The path of the node shown in the given row.
+    pub fn path_of_row(&self, row: usize) -> Option<Vec<usize>> {
+        self.state.path_of(row)
+    }
+
+    /// The row showing the node at the given path, if it is visible.
+    pub fn row_of_path(&self, path: &[usize]) -> Option<usize> {
+        self.state.row_of(path)
+    }
+
+    /// Tell the tables showing this view that the values of the node at the given path
+    /// changed.
+    pub fn node_changed(&self, _ctx: &UI, path: &[usize]) {
+        if let Some(row) = self.state.row_of(path) {
+            self.state.notify_changed(row);
+        }
+    }
+
+    /// Tell the tables showing this view that the children of the node at the given path
+    /// changed. Use the empty path if the top-level nodes changed.
+    ///
+    /// The descendants of the node are shown collapsed, as the node's new children may be
+    /// unrelated to its old ones.
+    pub fn children_changed(&self, _ctx: &UI, path: &[usize]) {
+        self.state.children_changed(path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    /// Nodes have `3 - depth` children, and are labelled with their path.
+    struct Shrinking;
+
+    impl TreeModel for Shrinking {
+        fn num_columns(&self) -> i32 {
+            1
+        }
+
+        fn column_type(&self, _column: i32) -> TableValueType {
+            TableValueType::String
+        }
+
+        fn num_children(&self, path: &[usize]) -> usize {
+            3usize.saturating_sub(path.len())
+        }
+
+        fn cell_value(&self, path: &[usize], _column: i32) -> TableValue {
+            let labels: Vec<String> = path.iter().map(|index| index.to_string()).collect();
+            TableValue::String(labels.join("."))
+        }
+    }
+
+    fn labels(state: &TreeState<Shrinking>) -> Vec<String> {
+        (0..state.num_rows())
+            .map(|row| match state.cell_value(row, TREE_COLUMNS_START) {
+                TableValue::String(text) => text,
+                _ => unreachable!(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn expanding_and_collapsing_nodes() {
+        let state = TreeState::new(Shrinking, TreeToggle::Button);
+        assert_eq!(labels(&state), vec!["0", "1", "2"]);
+
+        state.expand(&[1]);
+        assert_eq!(labels(&state), vec!["0", "1", "    1.0", "    1.1", "2"]);
+
+        state.expand(&[1, 0]);
+        assert_eq!(state.num_rows(), 6);
+        assert_eq!(state.path_of(3), Some(vec![1, 0, 0]));
+
+        state.collapse(&[1]);
+        assert_eq!(labels(&state), vec!["0", "1", "2"]);
+
+        // Nested expansion is remembered while the parent is collapsed.
+        state.expand(&[1]);
+        assert_eq!(state.num_rows(), 6);
+    }
+
+    #[test]
+    fn toggle_column_expands_nodes_with_children() {
+        let state = TreeState::new(Shrinking, TreeToggle::Checkbox);
+        state.set_cell_value(0, TOGGLE_COLUMN, Some(TableValue::Int(1)));
+        assert!(state.is_expanded(&[0]));
+        match state.cell_value(0, TOGGLE_COLUMN) {
+            TableValue::Int(1) => {}
+            other => panic!("unexpected toggle value {:?}", other),
+        }
+
+        // [0, 0] has a child, but [0, 0, 0] does not.
+        state.set_cell_value(1, TOGGLE_COLUMN, Some(TableValue::Int(1)));
+        state.set_cell_value(2, TOGGLE_COLUMN, Some(TableValue::Int(1)));
+        assert!(!state.is_expanded(&[0, 0, 0]));
+        assert_eq!(state.num_rows(), 3 + 2 + 1);
+
+        state.set_cell_value(0, TOGGLE_COLUMN, Some(TableValue::Int(0)));
+        assert_eq!(state.num_rows(), 3);
+    }
+
+    #[test]
+    fn changed_children_are_shown_collapsed() {
+        let state = TreeState::new(Shrinking, TreeToggle::Button);
+        state.expand(&[1]);
+        state.expand(&[1, 0]);
+        state.expand(&[2]);
+        assert_eq!(state.num_rows(), 3 + 2 + 1 + 2);
+
+        state.children_changed(&[1]);
+        assert!(state.is_expanded(&[1]));
+        assert!(!state.is_expanded(&[1, 0]));
+        assert!(state.is_expanded(&[2]));
+        assert_eq!(state.num_rows(), 3 + 2 + 2);
+        assert_eq!(state.path_of(3), Some(vec![1, 1]));
+
+        state.children_changed(&[]);
+        assert_eq!(labels(&state), vec!["0", "1", "2"]);
+    }
+
+    /// Top-level nodes, each with a single child.
+    struct Resizable(Cell<usize>);
+
+    impl TreeModel for Resizable {
+        fn num_columns(&self) -> i32 {
+            1
+        }
+
+        fn column_type(&self, _column: i32) -> TableValueType {
+            TableValueType::Int
+        }
+
+        fn num_children(&self, path: &[usize]) -> usize {
+            match path.len() {
+                0 => self.0.get(),
+                1 => 1,
+                _ => 0,
+            }
+        }
+
+        fn cell_value(&self, path: &[usize], _column: i32) -> TableValue {
+            TableValue::Int(path.len() as i32)
+        }
+    }
+
+    #[test]
+    fn refreshing_keeps_the_rows_in_both() {
+        let state = TreeState::new(Resizable(Cell::new(3)), TreeToggle::Button);
+        state.expand(&[2]);
+        assert_eq!(state.num_rows(), 4);
+
+        state.model.0.set(1);
+        state.refresh();
+        assert_eq!(*state.rows.borrow(), vec![vec![0]]);
+
+        state.model.0.set(4);
+        state.expand(&[0]);
+        state.refresh();
+        assert_eq!(
+            *state.rows.borrow(),
+            vec![vec![0], vec![0, 0], vec![1], vec![2], vec![2, 0], vec![3]]
+        );
+    }
+
+    #[test]
+    fn missing_rows_are_shown_empty() {
+        let state = TreeState::new(Shrinking, TreeToggle::Checkbox);
+        match state.cell_value(3, TOGGLE_COLUMN) {
+            TableValue::Int(0) => {}
+            other => panic!("unexpected value {:?} for a missing row", other),
+        }
+    }
+}
@@ -0,0 +1,173 @@
+use image::Image;
+use std::ffi::{CStr, CString};
+use std::mem::ManuallyDrop;
+use std::os::raw::c_int;
+use std::rc::Rc;
+use ui_sys::{self, uiTableValue, uiTableValueType};
+
+/// The kinds of data a column of a `TableModel` can hold.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TableValueType {
+    /// Text, used by text columns and as the label of button columns.
+    String,
+    /// An `Image`, used by image columns.
+    Image,
+    /// An integer, used by checkbox columns (`0` or `1`), progress bar columns
+    /// (`0` to `100`, or `-1` for indeterminate) and editability columns.
+    Int,
+    /// A color, used for text and row background colors.
+    Color,
+}
+
+impl TableValueType {
+    pub(crate) fn into_ui_table_value_type(self) -> uiTableValueType {
+        return match self {
+            TableValueType::String => ui_sys::uiTableValueTypeString,
+            TableValueType::Image => ui_sys::uiTableValueTypeImage,
+            TableValueType::Int => ui_sys::uiTableValueTypeInt,
+            TableValueType::Color => ui_sys::uiTableValueTypeColor,
+        } as uiTableValueType;
+    }
+}
+
+thread_local! {
+    /// The image shown in image cells which have no value. Tables may draw it at any time, so
+    /// it is never freed.
+    static EMPTY_IMAGE: ManuallyDrop<Rc<Image>> =
+        ManuallyDrop::new(Rc::new(Image::transparent_pixel()))
+}
+
+/// A single cell of a `TableModel`.
+///
+/// Image values hold a reference-counted `Image`. The table only borrows the image while
+/// drawing, so the image a model last returned for each cell is kept alive until it returns
+/// another, or the row is deleted.
+#[derive(Clone, Debug)]
+pub enum TableValue {
+    String(String),
+    Image(Rc<Image>),
+    Int(i32),
+    Color { r: f64, g: f64, b: f64, a: f64 },
+}
+
+impl TableValue {
+    /// Returns the type of this value.
+    pub fn value_type(&self) -> TableValueType {
+        match *self {
+            TableValue::String(_) => TableValueType::String,
+            TableValue::Image(_) => TableValueType::Image,
+            TableValue::Int(_) => TableValueType::Int,
+            TableValue::Color { .. } => TableValueType::Color,
+        }
+    }
+
+    /// An empty value of the given type: an empty string, zero, a transparent color or a
+    /// transparent image. Shown in place of cells which a model cannot provide.
+    pub(crate) fn empty(value_type: TableValueType) -> TableValue {
+        match value_type {
+            TableValueType::String => TableValue::String(String::new()),
+            TableValueType::Image => TableValue::Image(EMPTY_IMAGE.with(|image| Rc::clone(image))),
+            TableValueType::Int => TableValue::Int(0),
+            TableValueType::Color => TableValue::Color {
+                r: 0.0,
+                g: 0.0,
+                b: 0.0,
+                a: 0.0,
+            },
+        }
+    }
+
+    /// Allocates a new `uiTableValue` holding this value. Ownership passes to the caller.
+    ///
+    /// Strings are cut off at their first NUL byte, which C strings cannot hold, since this
+    /// is called from inside the UI library's callbacks, where it must not panic.
+    pub(crate) fn as_ui_table_value(&self) -> *mut uiTableValue {
+        unsafe {
+            match *self {
+                TableValue::String(ref s) => {
+                    let c_string = to_c_string_lossy(s);
+                    ui_sys::uiNewTableValueString(c_string.as_ptr())
+                }
+                TableValue::Image(ref image) => ui_sys::uiNewTableValueImage(image.ptr()),
+                TableValue::Int(i) => ui_sys::uiNewTableValueInt(i as c_int),
+                TableValue::Color { r, g, b, a } => ui_sys::uiNewTableValueColor(r, g, b, a),
+            }
+        }
+    }
+
+    /// Copies a value handed to us by libui. Images cannot be copied, since they are owned
+    /// elsewhere, so those produce `None`.
+    ///
+    /// # Unsafety
+    /// The pointer must point to a valid `uiTableValue`.
+    pub(crate) unsafe fn from_ui_table_value(value: *const uiTableValue) -> Option<TableValue> {
+        let ty = ui_sys::uiTableValueGetType(value);
+        if ty == ui_sys::uiTableValueTypeString as uiTableValueType {
+            Some(TableValue::String(
+                CStr::from_ptr(ui_sys::uiTableValueString(value))
+                    .to_string_lossy()
+                    .into_owned(),
+            ))
+        } else if ty == ui_sys::uiTableValueTypeInt as uiTableValueType {
+            Some(TableValue::Int(ui_sys::uiTableValueInt(value)))
+        } else if ty == ui_sys::uiTableValueTypeColor as uiTableValueType {
+            let (mut r, mut g, mut b, mut a) = (0.0, 0.0, 0.0, 0.0);
+            ui_sys::uiTableValueColor(value, &mut r, &mut g, &mut b, &mut a);
+            Some(TableValue::Color { r, g, b, a })
+        } else {
+            None
+        }
+    }
+}
+
+/// Converts the given text to a C string, cutting it off at its first NUL byte.
+fn to_c_string_lossy(text: &str) -> CString {
+    let bytes = text.as_bytes();
+    let end = bytes
+        .iter()
+        .position(|&byte| byte == 0)
+        .unwrap_or(bytes.len());
+    CString::new(&bytes[..end]).unwrap_or_default()
+}
+
+impl From<String> for TableValue {
+    fn from(value: String) -> TableValue {
+        TableValue::String(value)
+    }
+}
+
+impl<'a> From<&'a str> for TableValue {
+    fn from(value: &'a str) -> TableValue {
+        TableValue::String(value.to_string())
+    }
+}
+
+impl From<i32> for TableValue {
+    fn from(value: i32) -> TableValue {
+        TableValue::Int(value)
+    }
+}
+
+impl From<bool> for TableValue {
+    fn from(value: bool) -> TableValue {
+        TableValue::Int(value as i32)
+    }
+}
+
+impl From<Rc<Image>> for TableValue {
+    fn from(value: Rc<Image>) -> TableValue {
+        TableValue::Image(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strings_are_cut_off_at_nul() {
+        assert_eq!(to_c_string_lossy("plain").to_bytes(), b"plain");
+        assert_eq!(to_c_string_lossy("cut\0off").to_bytes(), b"cut");
+        assert_eq!(to_c_string_lossy("\0").to_bytes(), b"");
+    }
+}
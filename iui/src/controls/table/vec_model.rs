@@ -0,0 +1,884 @@
+use super::{TableModel, TableModelHandle, TableValue, TableValueType, WeakTableModelHandle};
+use std::cell::{Ref, RefCell};
+use std::cmp::Ordering;
+use std::mem;
+use std::rc::{Rc, Weak};
+use ui::UI;
+
+/// Maps a Rust value onto the columns of a table row, so that a `Vec` of such values can be
+/// shown in a `Table` through a `VecTableModel`.
+pub trait TableRow {
+    /// The number of model columns each row has.
+    fn num_columns() -> i32;
+    /// The type of the values in the given model column.
+    fn column_type(column: i32) -> TableValueType;
+    /// The value of the given column for this row.
+    fn cell_value(&self, column: i32) -> TableValue;
+    /// Called when the user edits a cell of this row. `value` is `None` when a button
+    /// is clicked.
+    fn set_cell_value(&mut self, _column: i32, _value: Option<TableValue>) {}
+}
+
+/// A change to the rows of a model or view, reported to the tables showing it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum RowChange {
+    Inserted(usize),
+    Changed(usize),
+    Deleted(usize),
+}
+
+/// The rows shared by a `VecTableModel` and all views of it.
+///
+/// Views hold the store, so it only holds weak references back to them.
+struct VecStore<T> {
+    rows: RefCell<Vec<T>>,
+    views: RefCell<Vec<Weak<View<T>>>>,
+}
+
+impl<T: TableRow + 'static> VecStore<T> {
+    fn new(rows: Vec<T>) -> Rc<VecStore<T>> {
+        Rc::new(VecStore {
+            rows: RefCell::new(rows),
+            views: RefCell::new(Vec::new()),
+        })
+    }
+
+    fn live_views(&self) -> Vec<Rc<View<T>>> {
+        self.views
+            .borrow()
+            .iter()
+            .filter_map(Weak::upgrade)
+            .collect()
+    }
+
+    /// Tell every view that a row changed. The rows must not be borrowed while doing so,
+    /// since the tables will read them.
+    fn notify(&self, change: RowChange) {
+        for view in self.live_views() {
+            view.source_changed(change);
+        }
+    }
+
+    /// Replace every row, telling each view only about the rows it shows which differ.
+    fn replace(&self, rows: Vec<T>) {
+        let views = self.live_views();
+        let old_mappings: Vec<Vec<usize>> = views
+            .iter()
+            .map(|view| view.state.current_mapping())
+            .collect();
+        let old_rows = mem::replace(&mut *self.rows.borrow_mut(), rows);
+        drop(old_rows);
+        for (view, old_mapping) in views.iter().zip(old_mappings) {
+            view.rebuild_from(old_mapping, true);
+        }
+    }
+}
+
+type Filter<T> = Box<dyn Fn(&T) -> bool>;
+type Order<T> = Box<dyn Fn(&T, &T) -> Ordering>;
+
+/// The state of a view onto a `VecStore`: which source rows it shows, and in what order.
+struct ViewState<T> {
+    store: Rc<VecStore<T>>,
+    /// The source index of each row of the view, or `None` if the view shows every source
+    /// row in order.
+    mapping: RefCell<Option<Vec<usize>>>,
+    filter: RefCell<Option<Filter<T>>>,
+    order: RefCell<Option<Order<T>>>,
+}
+
+impl<T: TableRow + 'static> ViewState<T> {
+    fn len(&self) -> usize {
+        match *self.mapping.borrow() {
+            Some(ref mapping) => mapping.len(),
+            None => self.store.rows.borrow().len(),
+        }
+    }
+
+    /// The source index of each row of the view.
+    fn current_mapping(&self) -> Vec<usize> {
+        match *self.mapping.borrow() {
+            Some(ref mapping) => mapping.clone(),
+            None => (0..self.store.rows.borrow().len()).collect(),
+        }
+    }
+
+    fn source_index(&self, row: usize) -> Option<usize> {
+        match *self.mapping.borrow() {
+            Some(ref mapping) => mapping.get(row).cloned(),
+            None if row < self.store.rows.borrow().len() => Some(row),
+            None => None,
+        }
+    }
+
+    fn accepts(&self, row: &T) -> bool {
+        match *self.filter.borrow() {
+            Some(ref filter) => filter(row),
+            None => true,
+        }
+    }
+
+    /// Compares two source rows by the view's order, falling back to their source order.
+    fn compare(&self, rows: &[T], a: usize, b: usize) -> Ordering {
+        match *self.order.borrow() {
+            Some(ref order) => order(&rows[a], &rows[b]).then(a.cmp(&b)),
+            None => a.cmp(&b),
+        }
+    }
+
+    /// Finds where the given source row belongs in a mapping which does not contain it.
+    fn insertion_point(&self, rows: &[T], mapping: &[usize], index: usize) -> usize {
+        match mapping.binary_search_by(|&probe| self.compare(rows, probe, index)) {
+            Ok(position) | Err(position) => position,
+        }
+    }
+
+    /// Computes the full mapping for the current filter and order.
+    fn build_mapping(&self) -> Vec<usize> {
+        let rows = self.store.rows.borrow();
+        let mut mapping: Vec<usize> = (0..rows.len())
+            .filter(|&i| self.accepts(&rows[i]))
+            .collect();
+        mapping.sort_by(|&a, &b| self.compare(&rows, a, b));
+        mapping
+    }
+}
+
+impl<T: TableRow + 'static> TableModel for ViewState<T> {
+    fn num_columns(&self) -> i32 {
+        T::num_columns()
+    }
+
+    fn column_type(&self, column: i32) -> TableValueType {
+        T::column_type(column)
+    }
+
+    fn num_rows(&self) -> i32 {
+        self.len() as i32
+    }
+
+    fn cell_value(&self, row: i32, column: i32) -> TableValue {
+        let rows = self.store.rows.borrow();
+        match self
+            .source_index(row as usize)
+            .and_then(|index| rows.get(index))
+        {
+            Some(row) => row.cell_value(column),
+            // The table asked for a row it was never told about; show it as empty rather than
+            // panicking, which would abort inside the UI library's callback.
+            None => TableValue::empty(T::column_type(column)),
+        }
+    }
+
+    fn set_cell_value(&self, row: i32, column: i32, value: Option<TableValue>) {
+        if let Some(index) = self.source_index(row as usize) {
+            self.store.rows.borrow_mut()[index].set_cell_value(column, value);
+            self.store.notify(RowChange::Changed(index));
+        }
+    }
+}
+
+/// A view registered with a `VecStore`, along with the place its changes are reported to.
+///
+/// The view is the model of its table, which keeps it alive, and so attached to the store,
+/// for as long as the table may show it.
+struct View<T> {
+    state: Rc<ViewState<T>>,
+    sink: RefCell<Option<Box<dyn Fn(RowChange)>>>,
+}
+
+impl<T: TableRow + 'static> View<T> {
+    fn new(
+        store: &Rc<VecStore<T>>,
+        filter: Option<Filter<T>>,
+        order: Option<Order<T>>,
+    ) -> Rc<View<T>> {
+        let state = Rc::new(ViewState {
+            store: store.clone(),
+            mapping: RefCell::new(None),
+            filter: RefCell::new(filter),
+            order: RefCell::new(order),
+        });
+        if state.filter.borrow().is_some() || state.order.borrow().is_some() {
+            *state.mapping.borrow_mut() = Some(state.build_mapping());
+        }
+        let view = Rc::new(View {
+            state,
+            sink: RefCell::new(None),
+        });
+        let mut views = store.views.borrow_mut();
+        views.retain(|view| view.upgrade().is_some());
+        views.push(Rc::downgrade(&view));
+        drop(views);
+        view
+    }
+
+    /// Set where the view's changes are reported to.
+    fn set_sink(&self, sink: Box<dyn Fn(RowChange)>) {
+        *self.sink.borrow_mut() = Some(sink);
+    }
+
+    fn report(&self, change: RowChange) {
+        if let Some(ref sink) = *self.sink.borrow() {
+            sink(change);
+        }
+    }
+
+    /// Updates the view after a change to its source, reporting each step as it is made so
+    /// that the view never disagrees with what the tables were told.
+    fn source_changed(&self, change: RowChange) {
+        match change {
+            RowChange::Inserted(index) => self.source_inserted(index),
+            RowChange::Changed(index) => self.source_changed_at(index),
+            RowChange::Deleted(index) => self.source_deleted(index),
+        }
+    }
+
+    fn source_inserted(&self, index: usize) {
+        let inserted = {
+            let rows = self.state.store.rows.borrow();
+            let mut mapping = self.state.mapping.borrow_mut();
+            match *mapping {
+                None => Some(index),
+                Some(ref mut mapping) => {
+                    for source in mapping.iter_mut() {
+                        if *source >= index {
+                            *source += 1;
+                        }
+                    }
+                    if self.state.accepts(&rows[index]) {
+                        let position = self.state.insertion_point(&rows, mapping, index);
+                        mapping.insert(position, index);
+                        Some(position)
+                    } else {
+                        None
+                    }
+                }
+            }
+        };
+        if let Some(position) = inserted {
+            self.report(RowChange::Inserted(position));
+        }
+    }
+
+    fn source_deleted(&self, index: usize) {
+        let deleted = {
+            let mut mapping = self.state.mapping.borrow_mut();
+            match *mapping {
+                None => Some(index),
+                Some(ref mut mapping) => {
+                    let position = mapping.iter().position(|&source| source == index);
+                    if let Some(position) = position {
+                        mapping.remove(position);
+                    }
+                    for source in mapping.iter_mut() {
+                        if *source > index {
+                            *source -= 1;
+                        }
+                    }
+                    position
+                }
+            }
+        };
+        if let Some(position) = deleted {
+            self.report(RowChange::Deleted(position));
+        }
+    }
+
+    fn source_changed_at(&self, index: usize) {
+        let (old, new) = {
+            let rows = self.state.store.rows.borrow();
+            let mut mapping = self.state.mapping.borrow_mut();
+            match *mapping {
+                None => (Some(index), Some(index)),
+                Some(ref mut mapping) => {
+                    let old = mapping.iter().position(|&source| source == index);
+                    if let Some(old) = old {
+                        mapping.remove(old);
+                    }
+                    let new = if self.state.accepts(&rows[index]) {
+                        Some(self.state.insertion_point(&rows, mapping, index))
+                    } else {
+                        None
+                    };
+                    if old.is_some() && old == new {
+                        mapping.insert(old.unwrap(), index);
+                    }
+                    (old, new)
+                }
+            }
+        };
+
+        if old.is_some() && old == new {
+            self.report(RowChange::Changed(old.unwrap()));
+            return;
+        }
+        if let Some(old) = old {
+            self.report(RowChange::Deleted(old));
+        }
+        if let Some(new) = new {
+            if let Some(ref mut mapping) = *self.state.mapping.borrow_mut() {
+                mapping.insert(new, index);
+            }
+            self.report(RowChange::Inserted(new));
+        }
+    }
+
+    /// Rebuilds the view after its filter or order changed.
+    fn rebuild(&self) {
+        self.rebuild_from(self.state.current_mapping(), false);
+    }
+
+    /// Rebuilds the view from the given mapping, which it showed until now, to the mapping for
+    /// its current filter and order.
+    ///
+    /// Only the difference is reported: rows past the end of the new mapping are deleted,
+    /// rows past the end of the old one are inserted, and the rows in both are changed where
+    /// they now show a different source row, or all of them if the source rows were replaced.
+    fn rebuild_from(&self, old: Vec<usize>, rows_replaced: bool) {
+        let target = self.state.build_mapping();
+        let common = old.len().min(target.len());
+
+        *self.state.mapping.borrow_mut() = Some(old.clone());
+        for position in (common..old.len()).rev() {
+            if let Some(ref mut mapping) = *self.state.mapping.borrow_mut() {
+                mapping.pop();
+            }
+            self.report(RowChange::Deleted(position));
+        }
+
+        if let Some(ref mut mapping) = *self.state.mapping.borrow_mut() {
+            mapping.copy_from_slice(&target[..common]);
+        }
+        for position in 0..common {
+            if rows_replaced || old[position] != target[position] {
+                self.report(RowChange::Changed(position));
+            }
+        }
+
+        for (position, &index) in target.iter().enumerate().skip(common) {
+            if let Some(ref mut mapping) = *self.state.mapping.borrow_mut() {
+                mapping.push(index);
+            }
+            self.report(RowChange::Inserted(position));
+        }
+
+        if self.state.filter.borrow().is_none() && self.state.order.borrow().is_none() {
+            *self.state.mapping.borrow_mut() = None;
+        }
+    }
+}
+
+impl<T: TableRow + 'static> TableModel for View<T> {
+    fn num_columns(&self) -> i32 {
+        self.state.num_columns()
+    }
+
+    fn column_type(&self, column: i32) -> TableValueType {
+        self.state.column_type(column)
+    }
+
+    fn num_rows(&self) -> i32 {
+        self.state.num_rows()
+    }
+
+    fn cell_value(&self, row: i32, column: i32) -> TableValue {
+        self.state.cell_value(row, column)
+    }
+
+    fn set_cell_value(&self, row: i32, column: i32, value: Option<TableValue>) {
+        self.state.set_cell_value(row, column, value)
+    }
+}
+
+/// Reports a view's changes to the tables showing it. The handle is weak, since the view is
+/// the handle's model.
+fn handle_sink(handle: &TableModelHandle) -> Box<dyn Fn(RowChange)> {
+    let handle: WeakTableModelHandle = handle.downgrade();
+    Box::new(move |change| {
+        let handle = match handle.upgrade() {
+            Some(handle) => handle,
+            None => return,
+        };
+        match change {
+            RowChange::Inserted(index) => handle.notify_row_inserted(index as i32),
+            RowChange::Changed(index) => handle.notify_row_changed(index as i32),
+            RowChange::Deleted(index) => handle.notify_row_deleted(index as i32),
+        }
+    })
+}
+
+/// A `Vec` of rows which can be shown in a `Table`, and which keeps every table showing it,
+/// or any view of it, up to date as it is changed.
+///
+/// The rows are read by the tables whenever they are drawn, so they are only exposed
+/// through short-lived borrows. Cloning a `VecTableModel` produces another reference to
+/// the same rows.
+///
+/// ```no_run
+/// # use iui::prelude::*;
+/// # use iui::controls::{Table, TableRow, TableTextColumn, TableValue, TableValueType,
+/// #     VecTableModel};
+/// struct Person { name: String, age: i32 }
+///
+/// impl TableRow for Person {
+///     fn num_columns() -> i32 { 2 }
+///     fn column_type(_column: i32) -> TableValueType { TableValueType::String }
+///     fn cell_value(&self, column: i32) -> TableValue {
+///         match column {
+///             0 => TableValue::String(self.name.clone()),
+///             _ => TableValue::String(self.age.to_string()),
+///         }
+///     }
+/// }
+///
+/// let ui = UI::init().unwrap();
+/// let people = VecTableModel::new(&ui, vec![]);
+/// let by_age = people.sorted_by(&ui, |a: &Person, b: &Person| a.age.cmp(&b.age));
+///
+/// let mut table = Table::new(&ui, by_age.handle());
+/// table.append_text_column(&ui, "Name", TableTextColumn::new(0));
+/// table.append_text_column(&ui, "Age", TableTextColumn::new(1));
+///
+/// people.push(&ui, Person { name: "Ada".into(), age: 36 });
+/// ```
+pub struct VecTableModel<T> {
+    store: Rc<VecStore<T>>,
+    handle: TableModelHandle,
+}
+
+impl<T> Clone for VecTableModel<T> {
+    fn clone(&self) -> VecTableModel<T> {
+        VecTableModel {
+            store: self.store.clone(),
+            handle: self.handle.clone(),
+        }
+    }
+}
+
+impl<T: TableRow + 'static> VecTableModel<T> {
+    /// Create a new model holding the given rows.
+    pub fn new(ctx: &UI, rows: Vec<T>) -> VecTableModel<T> {
+        let store = VecStore::new(rows);
+        let view = View::new(&store, None, None);
+        let handle = TableModelHandle::new(ctx, view.clone());
+        view.set_sink(handle_sink(&handle));
+        VecTableModel { store, handle }
+    }
+
+    /// The handle used to show this model, in the order of the underlying `Vec`, in a `Table`.
+    pub fn handle(&self) -> &TableModelHandle {
+        &self.handle
+    }
+
+    /// The number of rows.
+    pub fn len(&self) -> usize {
+        self.store.rows.borrow().len()
+    }
+
+    /// Returns `true` if there are no rows.
+    pub fn is_empty(&self) -> bool {
+        self.store.rows.borrow().is_empty()
+    }
+
+    /// Borrow the rows. The borrow must end before the model is changed.
+    pub fn rows<'a>(&'a self) -> Ref<'a, [T]> {
+        Ref::map(self.store.rows.borrow(), |rows| &rows[..])
+    }
+
+    /// Add a row to the end.
+    pub fn push(&self, ctx: &UI, row: T) {
+        let index = self.len();
+        self.insert(ctx, index, row);
+    }
+
+    /// Insert a row at the given index, shifting the following rows down.
+    ///
+    /// # Panics
+    /// Panics if `index` is greater than the number of rows.
+    pub fn insert(&self, _ctx: &UI, index: usize, row: T) {
+        self.store.rows.borrow_mut().insert(index, row);
+        self.store.notify(RowChange::Inserted(index));
+    }
+
+    /// Remove and return the row at the given index, shifting the following rows up.
+    ///
+    /// # Panics
+    /// Panics if `index` is out of bounds.
+    pub fn remove(&self, _ctx: &UI, index: usize) -> T {
+        let row = self.store.rows.borrow_mut().remove(index);
+        self.store.notify(RowChange::Deleted(index));
+        row
+    }
+
+    /// Replace the row at the given index, returning the old row.
+    ///
+    /// # Panics
+    /// Panics if `index` is out of bounds.
+    pub fn set(&self, ctx: &UI, index: usize, row: T) -> T {
+        let mut row = Some(row);
+        let mut old = None;
+        self.update(ctx, index, |current| {
+            old = row.take().map(|row| mem::replace(current, row));
+        });
+        old.unwrap()
+    }
+
+    /// Modify the row at the given index in place.
+    ///
+    /// # Panics
+    /// Panics if `index` is out of bounds.
+    pub fn update<F: FnOnce(&mut T)>(&self, _ctx: &UI, index: usize, f: F) {
+        f(&mut self.store.rows.borrow_mut()[index]);
+        self.store.notify(RowChange::Changed(index));
+    }
+
+    /// Remove every row.
+    pub fn clear(&self, ctx: &UI) {
+        self.replace(ctx, Vec::new());
+    }
+
+    /// Replace every row with the given rows.
+    pub fn replace(&self, _ctx: &UI, rows: Vec<T>) {
+        self.store.replace(rows);
+    }
+
+    /// Create a view of this model which shows its rows ordered by the given comparison.
+    /// Rows which compare equal stay in the order of the underlying `Vec`.
+    pub fn sorted_by<F>(&self, ctx: &UI, compare: F) -> VecTableView<T>
+    where
+        F: Fn(&T, &T) -> Ordering + 'static,
+    {
+        VecTableView::new(ctx, &self.store, None, Some(Box::new(compare)))
+    }
+
+    /// Create a view of this model which shows only the rows matching the given predicate.
+    pub fn filtered<F>(&self, ctx: &UI, predicate: F) -> VecTableView<T>
+    where
+        F: Fn(&T) -> bool + 'static,
+    {
+        VecTableView::new(ctx, &self.store, Some(Box::new(predicate)), None)
+    }
+}
+
+/// A sorted and/or filtered view of a `VecTableModel`, which can be shown in its own `Table`.
+///
+/// The view keeps a mapping from its rows to the rows of the model, and updates both the
+/// mapping and its tables whenever the model changes. Edits made through the view's tables
+/// are applied to the model, and so show up in every other view. A view stays attached to
+/// its model until it and every table showing it are gone. Cloning a `VecTableView`
+/// produces another reference to the same view.
+pub struct VecTableView<T> {
+    view: Rc<View<T>>,
+    handle: TableModelHandle,
+}
+
+impl<T> Clone for VecTableView<T> {
+    fn clone(&self) -> VecTableView<T> {
+        VecTableView {
+            view: self.view.clone(),
+            handle: self.handle.clone(),
+        }
+    }
+}
+
+impl<T: TableRow + 'static> VecTableView<T> {
+    fn new(
+        ctx: &UI,
+        store: &Rc<VecStore<T>>,
+        filter: Option<Filter<T>>,
+        order: Option<Order<T>>,
+    ) -> VecTableView<T> {
+        let view = View::new(store, filter, order);
+        let handle = TableModelHandle::new(ctx, view.clone());
+        VecTableView::attach(view, handle)
+    }
+
+    /// Report the changes to the given view to the tables showing the given handle to it.
+    fn attach(view: Rc<View<T>>, handle: TableModelHandle) -> VecTableView<T> {
+        view.set_sink(handle_sink(&handle));
+        VecTableView { view, handle }
+    }
+
+    /// The handle used to show this view in a `Table`.
+    pub fn handle(&self) -> &TableModelHandle {
+        &self.handle
+    }
+
+    /// The number of rows shown by the view.
+    pub fn len(&self) -> usize {
+        self.view.state.len()
+    }
+
+    /// Returns `true` if the view shows no rows.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The index in the underlying model of the given row of the view.
+    pub fn source_index(&self, row: usize) -> Option<usize> {
+        self.view.state.source_index(row)
+    }
+
+    /// Show only the rows matching the given predicate.
+    pub fn set_filter<F>(&self, _ctx: &UI, predicate: F)
+    where
+        F: Fn(&T) -> bool + 'static,
+    {
+        *self.view.state.filter.borrow_mut() = Some(Box::new(predicate));
+        self.view.rebuild();
+    }
+
+    /// Show every row of the model.
+    pub fn clear_filter(&self, _ctx: &UI) {
+        *self.view.state.filter.borrow_mut() = None;
+        self.view.rebuild();
+    }
+
+    /// Order the rows by the given comparison.
+    pub fn set_order<F>(&self, _ctx: &UI, compare: F)
+    where
+        F: Fn(&T, &T) -> Ordering + 'static,
+    {
+        *self.view.state.order.borrow_mut() = Some(Box::new(compare));
+        self.view.rebuild();
+    }
+
+    /// Show the rows in the order of the model.
+    pub fn clear_order(&self, _ctx: &UI) {
+        *self.view.state.order.borrow_mut() = None;
+        self.view.rebuild();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    impl TableRow for i32 {
+        fn num_columns() -> i32 {
+            1
+        }
+
+        fn column_type(_column: i32) -> TableValueType {
+            TableValueType::Int
+        }
+
+        fn cell_value(&self, _column: i32) -> TableValue {
+            TableValue::Int(*self)
+        }
+
+        fn set_cell_value(&mut self, _column: i32, value: Option<TableValue>) {
+            if let Some(TableValue::Int(value)) = value {
+                *self = value;
+            }
+        }
+    }
+
+    /// Registers a view whose notifications are replayed onto a plain `Vec`, checking at
+    /// every step that the view agrees with what was reported.
+    fn mirrored_view(
+        store: &Rc<VecStore<i32>>,
+        filter: Option<Filter<i32>>,
+        order: Option<Order<i32>>,
+    ) -> (Rc<View<i32>>, Rc<RefCell<Vec<i32>>>) {
+        let view = View::new(store, filter, order);
+        let state = view.state.clone();
+        let mirror = Rc::new(RefCell::new(
+            (0..state.len())
+                .map(|row| value_at(&state, row))
+                .collect::<Vec<_>>(),
+        ));
+        let sink = {
+            let state = state.clone();
+            let mirror = mirror.clone();
+            Box::new(move |change| {
+                let mut mirror = mirror.borrow_mut();
+                match change {
+                    RowChange::Inserted(row) => mirror.insert(row, value_at(&state, row)),
+                    RowChange::Changed(row) => mirror[row] = value_at(&state, row),
+                    RowChange::Deleted(row) => {
+                        mirror.remove(row);
+                    }
+                }
+                assert_eq!(mirror.len(), state.len());
+            })
+        };
+        view.set_sink(sink);
+        (view, mirror)
+    }
+
+    fn value_at(state: &ViewState<i32>, row: usize) -> i32 {
+        match state.cell_value(row as i32, 0) {
+            TableValue::Int(value) => value,
+            _ => unreachable!(),
+        }
+    }
+
+    fn contents(view: &View<i32>) -> Vec<i32> {
+        (0..view.state.len())
+            .map(|row| value_at(&view.state, row))
+            .collect()
+    }
+
+    fn push(store: &VecStore<i32>, value: i32) {
+        let index = store.rows.borrow().len();
+        store.rows.borrow_mut().push(value);
+        store.notify(RowChange::Inserted(index));
+    }
+
+    #[test]
+    fn sorted_view_tracks_changes() {
+        let store = VecStore::new(vec![5, 1, 4]);
+        let (view, mirror) = mirrored_view(&store, None, Some(Box::new(|a, b| a.cmp(b))));
+        assert_eq!(contents(&view), vec![1, 4, 5]);
+
+        push(&store, 3);
+        store.rows.borrow_mut()[0] = 0;
+        store.notify(RowChange::Changed(0));
+        store.rows.borrow_mut().remove(2);
+        store.notify(RowChange::Deleted(2));
+
+        assert_eq!(*store.rows.borrow(), vec![0, 1, 3]);
+        assert_eq!(contents(&view), vec![0, 1, 3]);
+        assert_eq!(*mirror.borrow(), vec![0, 1, 3]);
+    }
+
+    #[test]
+    fn filtered_view_tracks_changes() {
+        let store = VecStore::new(vec![1, 2, 3, 4]);
+        let (view, mirror) = mirrored_view(&store, Some(Box::new(|v| v % 2 == 0)), None);
+        assert_eq!(contents(&view), vec![2, 4]);
+
+        push(&store, 6);
+        store.rows.borrow_mut()[0] = 8;
+        store.notify(RowChange::Changed(0));
+        store.rows.borrow_mut()[1] = 7;
+        store.notify(RowChange::Changed(1));
+
+        assert_eq!(contents(&view), vec![8, 4, 6]);
+        assert_eq!(*mirror.borrow(), vec![8, 4, 6]);
+        assert_eq!(view.state.source_index(1), Some(3));
+    }
+
+    #[test]
+    fn edits_through_a_view_reach_every_view() {
+        let store = VecStore::new(vec![3, 2, 1]);
+        let (sorted, sorted_mirror) = mirrored_view(&store, None, Some(Box::new(|a, b| a.cmp(b))));
+        let (all, all_mirror) = mirrored_view(&store, None, None);
+
+        sorted.state.set_cell_value(0, 0, Some(TableValue::Int(10)));
+
+        assert_eq!(*store.rows.borrow(), vec![3, 2, 10]);
+        assert_eq!(*sorted_mirror.borrow(), vec![2, 3, 10]);
+        assert_eq!(*all_mirror.borrow(), contents(&all));
+    }
+
+    #[test]
+    fn rebuild_applies_new_filter_and_order() {
+        let store = VecStore::new(vec![4, 1, 3, 2]);
+        let (view, mirror) = mirrored_view(&store, None, None);
+
+        *view.state.filter.borrow_mut() = Some(Box::new(|v| *v > 1));
+        *view.state.order.borrow_mut() = Some(Box::new(|a, b| b.cmp(a)));
+        view.rebuild();
+        assert_eq!(*mirror.borrow(), vec![4, 3, 2]);
+
+        *view.state.filter.borrow_mut() = None;
+        *view.state.order.borrow_mut() = None;
+        view.rebuild();
+        assert_eq!(*mirror.borrow(), vec![4, 1, 3, 2]);
+        assert!(view.state.mapping.borrow().is_none());
+    }
+
+    #[test]
+    fn rebuilds_report_only_the_difference() {
+        let store = VecStore::new(vec![4, 1, 3, 2]);
+        let (view, mirror) = mirrored_view(&store, None, None);
+        let changes = Rc::new(RefCell::new(Vec::new()));
+        {
+            let mirror_sink = view.sink.borrow_mut().take().unwrap();
+            let changes = changes.clone();
+            view.set_sink(Box::new(move |change| {
+                changes.borrow_mut().push(change);
+                mirror_sink(change);
+            }));
+        }
+
+        *view.state.order.borrow_mut() = Some(Box::new(|a, b| b.cmp(a)));
+        view.rebuild();
+        assert_eq!(*mirror.borrow(), vec![4, 3, 2, 1]);
+        assert_eq!(
+            changes.borrow_mut().split_off(0),
+            vec![
+                RowChange::Changed(1),
+                RowChange::Changed(2),
+                RowChange::Changed(3),
+            ]
+        );
+
+        *view.state.filter.borrow_mut() = Some(Box::new(|v| *v > 2));
+        view.rebuild();
+        assert_eq!(*mirror.borrow(), vec![4, 3]);
+        assert_eq!(
+            changes.borrow_mut().split_off(0),
+            vec![RowChange::Deleted(3), RowChange::Deleted(2)]
+        );
+
+        store.replace(vec![5, 6, 7]);
+        assert_eq!(*mirror.borrow(), vec![7, 6, 5]);
+        assert_eq!(
+            changes.borrow_mut().split_off(0),
+            vec![
+                RowChange::Changed(0),
+                RowChange::Changed(1),
+                RowChange::Inserted(2),
+            ]
+        );
+    }
+
+    #[test]
+    fn missing_rows_are_shown_empty() {
+        let store = VecStore::new(vec![1]);
+        let (view, _) = mirrored_view(&store, None, None);
+        assert_eq!(value_at(&view.state, 0), 1);
+        assert_eq!(value_at(&view.state, 1), 0);
+    }
+
+    #[test]
+    fn stores_are_freed_with_their_views() {
+        let store = VecStore::new(vec![1, 2]);
+        let (view, _) = mirrored_view(&store, None, None);
+        let weak = Rc::downgrade(&store);
+        drop(store);
+        assert!(weak.upgrade().is_some());
+        drop(view);
+        assert!(weak.upgrade().is_none());
+    }
+
+    #[test]
+    fn table_views_detach_once_dropped() {
+        let store = VecStore::new(vec![2, 1]);
+        let live_views = || {
+            store
+                .views
+                .borrow()
+                .iter()
+                .filter(|view| view.upgrade().is_some())
+                .count()
+        };
+        let view = View::new(&store, None, Some(Box::new(|a, b| a.cmp(b))));
+        let handle = TableModelHandle::unregistered(view.clone());
+        let table_view = VecTableView::attach(view, handle);
+        assert_eq!(table_view.source_index(0), Some(1));
+
+        let copy = table_view.clone();
+        drop(table_view);
+        assert_eq!(live_views(), 1);
+        drop(copy);
+        assert_eq!(live_views(), 0);
+
+        // The unregistered handle would panic if it were still notified.
+        push(&store, 3);
+    }
+}
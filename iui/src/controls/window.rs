@@ -1,7 +1,7 @@
 //! Functionality related to creating, managing, and destroying GUI windows.
 
-use callback_helpers::{from_void_ptr, to_heap_ptr};
-use controls::Control;
+use callback_helpers::{from_void_ptr, register_callback};
+use controls::{ownership, Control};
 use std::cell::RefCell;
 use std::ffi::{CStr, CString};
 use std::mem;
@@ -65,7 +65,7 @@ impl Window {
     /// Get the current title of the window.
     pub fn title(&self, _ctx: &UI) -> String {
         unsafe {
-            CStr::from_ptr(ui_sys::uiWindowTitle(self.uiWindow))
+            CStr::from_ptr(ui_sys::uiWindowTitle(self.ptr()))
                 .to_string_lossy()
                 .into_owned()
         }
@@ -73,14 +73,14 @@ impl Window {
 
     /// Get a reference to the current title of the window.
     pub fn title_ref(&self, _ctx: &UI) -> &CStr {
-        unsafe { &CStr::from_ptr(ui_sys::uiWindowTitle(self.uiWindow)) }
+        unsafe { &CStr::from_ptr(ui_sys::uiWindowTitle(self.ptr())) }
     }
 
     /// Set the window's title to the given string.
     pub fn set_title(&mut self, _ctx: &UI, title: &str) {
         unsafe {
             let c_string = CString::new(title.as_bytes().to_vec()).unwrap();
-            ui_sys::uiWindowSetTitle(self.uiWindow, c_string.as_ptr())
+            ui_sys::uiWindowSetTitle(self.ptr(), c_string.as_ptr())
         }
     }
 
@@ -96,7 +96,7 @@ impl Window {
         where
             G: FnMut(&mut Window),
         {
-            let mut window = Window { uiWindow: window };
+            let mut window = unsafe { Window::from_raw(window) };
             unsafe {
                 from_void_ptr::<G>(data)(&mut window);
             }
@@ -104,28 +104,101 @@ impl Window {
         }
 
         unsafe {
-            ui_sys::uiWindowOnClosing(self.uiWindow, Some(c_callback::<F>), to_heap_ptr(callback));
+            ui_sys::uiWindowOnClosing(
+                self.ptr(),
+                Some(c_callback::<F>),
+                register_callback(self.ptr() as *mut c_void, "on_closing", callback),
+            );
         }
     }
 
+    /// Get the size of the window's content area, excluding its title bar, borders and menubar,
+    /// as `(width, height)`.
+    pub fn content_size(&self, _ctx: &UI) -> (c_int, c_int) {
+        let mut width: c_int = 0;
+        let mut height: c_int = 0;
+        unsafe { ui_sys::uiWindowContentSize(self.ptr(), &mut width, &mut height) }
+        (width, height)
+    }
+
+    /// Resize the window so that its content area has the given size.
+    pub fn set_content_size(&mut self, _ctx: &UI, width: c_int, height: c_int) {
+        unsafe { ui_sys::uiWindowSetContentSize(self.ptr(), width, height) }
+    }
+
+    /// Set a callback to be run when the size of the window's content area changes, whether
+    /// by the user resizing the window or by the program. It is passed the new size as
+    /// `(width, height)`.
+    pub fn on_content_size_changed<'ctx, F>(&mut self, _ctx: &'ctx UI, callback: F)
+    where
+        F: FnMut(&mut Window, (c_int, c_int)) + 'static,
+    {
+        extern "C" fn c_callback<G>(window: *mut uiWindow, data: *mut c_void)
+        where
+            G: FnMut(&mut Window, (c_int, c_int)),
+        {
+            let mut window = unsafe { Window::from_raw(window) };
+            let mut size: (c_int, c_int) = (0, 0);
+            unsafe {
+                ui_sys::uiWindowContentSize(window.uiWindow, &mut size.0, &mut size.1);
+                from_void_ptr::<G>(data)(&mut window, size);
+            }
+        }
+
+        unsafe {
+            ui_sys::uiWindowOnContentSizeChanged(
+                self.ptr(),
+                Some(c_callback::<F>),
+                register_callback(
+                    self.ptr() as *mut c_void,
+                    "on_content_size_changed",
+                    callback,
+                ),
+            );
+        }
+    }
+
+    /// Check whether or not this window fills the whole screen.
+    pub fn fullscreen(&self, _ctx: &UI) -> bool {
+        unsafe { ui_sys::uiWindowFullscreen(self.ptr()) != 0 }
+    }
+
+    /// Set whether or not this window fills the whole screen, hiding its title bar and
+    /// borders.
+    pub fn set_fullscreen(&mut self, _ctx: &UI, fullscreen: bool) {
+        unsafe { ui_sys::uiWindowSetFullscreen(self.ptr(), fullscreen as c_int) }
+    }
+
+    /// Check whether or not this window is drawn without a title bar and borders.
+    pub fn borderless(&self, _ctx: &UI) -> bool {
+        unsafe { ui_sys::uiWindowBorderless(self.ptr()) != 0 }
+    }
+
+    /// Set whether or not this window is drawn without a title bar and borders.
+    pub fn set_borderless(&mut self, _ctx: &UI, borderless: bool) {
+        unsafe { ui_sys::uiWindowSetBorderless(self.ptr(), borderless as c_int) }
+    }
+
     /// Check whether or not this window has margins around the edges.
     pub fn margined(&self, _ctx: &UI) -> bool {
-        unsafe { ui_sys::uiWindowMargined(self.uiWindow) != 0 }
+        unsafe { ui_sys::uiWindowMargined(self.ptr()) != 0 }
     }
 
     /// Set whether or not the window has margins around the edges.
     pub fn set_margined(&mut self, _ctx: &UI, margined: bool) {
-        unsafe { ui_sys::uiWindowSetMargined(self.uiWindow, margined as c_int) }
+        unsafe { ui_sys::uiWindowSetMargined(self.ptr(), margined as c_int) }
     }
 
     /// Sets the window's child widget. The window can only have one child widget at a time.
     pub fn set_child<T: Into<Control>>(&mut self, _ctx: &UI, child: T) {
-        unsafe { ui_sys::uiWindowSetChild(self.uiWindow, child.into().as_ui_control()) }
+        let control = child.into();
+        unsafe { ui_sys::uiWindowSetChild(self.ptr(), control.as_ui_control()) }
+        ownership::set_only_child(self.ptr() as *mut uiControl, control);
     }
 
     /// Allow the user to select an existing file.
     pub fn open_file(&self, _ctx: &UI) -> Option<PathBuf> {
-        let ptr = unsafe { ui_sys::uiOpenFile(self.uiWindow) };
+        let ptr = unsafe { ui_sys::uiOpenFile(self.ptr()) };
         if ptr.is_null() {
             return None;
         };
@@ -135,7 +208,7 @@ impl Window {
 
     /// Allow the user to select a new or existing file.
     pub fn save_file(&self, _ctx: &UI) -> Option<PathBuf> {
-        let ptr = unsafe { ui_sys::uiSaveFile(self.uiWindow) };
+        let ptr = unsafe { ui_sys::uiSaveFile(self.ptr()) };
         if ptr.is_null() {
             return None;
         };
@@ -149,7 +222,7 @@ impl Window {
         unsafe {
             let c_title = CString::new(title.as_bytes().to_vec()).unwrap();
             let c_description = CString::new(description.as_bytes().to_vec()).unwrap();
-            ui_sys::uiMsgBox(self.uiWindow, c_title.as_ptr(), c_description.as_ptr())
+            ui_sys::uiMsgBox(self.ptr(), c_title.as_ptr(), c_description.as_ptr())
         }
     }
 
@@ -159,23 +232,37 @@ impl Window {
         unsafe {
             let c_title = CString::new(title.as_bytes().to_vec()).unwrap();
             let c_description = CString::new(description.as_bytes().to_vec()).unwrap();
-            ui_sys::uiMsgBoxError(self.uiWindow, c_title.as_ptr(), c_description.as_ptr())
+            ui_sys::uiMsgBoxError(self.ptr(), c_title.as_ptr(), c_description.as_ptr())
         }
     }
 
     pub unsafe fn destroy_all_windows() {
-        WINDOWS.with(|windows| {
-            let mut windows = windows.borrow_mut();
-            for window in windows.drain(..) {
-                window.destroy();
-            }
-        })
+        let windows: Vec<Window> = WINDOWS.with(|windows| windows.borrow_mut().drain(..).collect());
+        for window in windows {
+            window.destroy();
+        }
     }
 
-    /// Destroys a Window. Any use of the control after this is use-after-free; therefore, this
-    /// is marked unsafe.
-    pub unsafe fn destroy(&self) {
+    /// Destroys a Window, along with all of its children. Any remaining handles to the window
+    /// or its children can no longer be used, and will panic if they are.
+    pub fn destroy(&self) {
         // Don't check for initialization here since this can be run during deinitialization.
-        ui_sys::uiControlDestroy(self.uiWindow as *mut ui_sys::uiControl)
+        if !self.is_destroyed() {
+            unsafe { ownership::destroy(self.uiWindow as *mut uiControl) }
+        }
     }
 }
+
+/// Stop keeping the given control alive as a window, if it is one, once it has been destroyed.
+pub(crate) fn forget_window(control: *mut uiControl) {
+    let removed: Vec<Window> = WINDOWS.with(|windows| {
+        let mut windows = windows.borrow_mut();
+        let (removed, kept) = windows
+            .drain(..)
+            .partition(|window| window.uiWindow as *mut uiControl == control);
+        *windows = kept;
+        removed
+    });
+    // Dropped outside of the borrow.
+    drop(removed);
+}
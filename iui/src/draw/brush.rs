@@ -127,6 +127,49 @@ pub struct SolidBrush {
     pub a: f64,
 }
 
+impl From<Color> for SolidBrush {
+    fn from(color: Color) -> SolidBrush {
+        SolidBrush {
+            r: color.r,
+            g: color.g,
+            b: color.b,
+            a: color.a,
+        }
+    }
+}
+
+impl From<Color> for Brush {
+    fn from(color: Color) -> Brush {
+        Brush::Solid(color.into())
+    }
+}
+
+/// A color, with each component ranging from `0.0` to `1.0`.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Color {
+    /// Red component of the color
+    pub r: f64,
+    /// Green component of the color
+    pub g: f64,
+    /// Blue component of the color
+    pub b: f64,
+    /// Alpha (α) component of the color (that is, opacity).
+    pub a: f64,
+}
+
+impl Color {
+    /// Create a new color from its components.
+    pub fn new(r: f64, g: f64, b: f64, a: f64) -> Color {
+        Color { r, g, b, a }
+    }
+}
+
+impl From<SolidBrush> for Color {
+    fn from(brush: SolidBrush) -> Color {
+        Color::new(brush.r, brush.g, brush.b, brush.a)
+    }
+}
+
 /// A brush that paints a linear gradient.
 #[derive(Clone, Debug)]
 pub struct LinearGradientBrush {
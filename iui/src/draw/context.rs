@@ -1,4 +1,7 @@
+use draw::text::TextLayout;
 use draw::{Brush, Path, StrokeParams, Transform};
+use ffi_tools;
+use std::ops::Deref;
 use ui_sys::{self, uiDrawContext};
 
 /// Drawing context, used to draw custom content on the screen.
@@ -13,6 +16,7 @@ impl DrawContext {
     /// If the pointer is invalid, this is memory-unsafe.
     /// If libui is not initialized, behavior will be inconsistent.
     pub unsafe fn from_ui_draw_context(ui_draw_context: *mut uiDrawContext) -> DrawContext {
+        ffi_tools::assert_main_thread();
         DrawContext {
             ui_draw_context: ui_draw_context,
         }
@@ -40,18 +44,57 @@ impl DrawContext {
         }
     }
 
+    /// Draw the given text layout on this DrawContext, with its top-left corner at the given
+    /// point.
+    pub fn draw_text(&self, layout: &TextLayout, x: f64, y: f64) {
+        unsafe { ui_sys::uiDrawText(self.ui_draw_context, layout.ptr(), x, y) }
+    }
+
     /// Transform this DrawContext by the given Transform.
     pub fn transform(&self, txform: &Transform) {
         unsafe { ui_sys::uiDrawTransform(self.ui_draw_context, txform.ptr()) }
     }
 
-    /// Open a modal allowing the user to save the contents of this DrawContext.
-    pub fn save(&self) {
+    /// Restrict all further drawing on this DrawContext to the inside of the given Path.
+    ///
+    /// Clipping can only be undone by restoring a saved state, so this is usually called on
+    /// the guard returned by `save`.
+    pub fn clip(&self, path: &Path) {
+        unsafe { ui_sys::uiDrawClip(self.ui_draw_context, path.ptr()) }
+    }
+
+    /// Save the current transform and clip of this DrawContext, returning a guard which
+    /// restores them when it is dropped. Saves may be nested.
+    ///
+    /// The guard dereferences to the DrawContext, so drawing can continue through it.
+    pub fn save<'a>(&'a self) -> DrawSaveGuard<'a> {
         unsafe { ui_sys::uiDrawSave(self.ui_draw_context) }
+        DrawSaveGuard { context: self }
     }
+}
+
+/// Restores the transform and clip of a DrawContext to what they were when `save` was called,
+/// once this guard is dropped.
+#[must_use = "the saved state is restored as soon as the guard is dropped"]
+pub struct DrawSaveGuard<'a> {
+    context: &'a DrawContext,
+}
+
+impl<'a> DrawSaveGuard<'a> {
+    /// Restore the saved state now, rather than at the end of the scope.
+    pub fn restore(self) {}
+}
+
+impl<'a> Deref for DrawSaveGuard<'a> {
+    type Target = DrawContext;
+
+    fn deref(&self) -> &DrawContext {
+        self.context
+    }
+}
 
-    /// Open a modal allowing the user to load the contents of a DrawContext onto this one.
-    pub fn restore(&self) {
-        unsafe { ui_sys::uiDrawRestore(self.ui_draw_context) }
+impl<'a> Drop for DrawSaveGuard<'a> {
+    fn drop(&mut self) {
+        unsafe { ui_sys::uiDrawRestore(self.context.ui_draw_context) }
     }
 }
@@ -0,0 +1,579 @@
+//! Fonts, and text which can be drawn on a `DrawContext`.
+//!
+//! Text is drawn in two steps. First, an `AttributedString` holds the text along with any
+//! styling, such as fonts and colors, applied to ranges of it. Then a `TextLayout` arranges
+//! the string into lines of a given width, using a default font for any text not otherwise
+//! styled, and can be drawn with `DrawContext::draw_text`.
+//!
+//! Ranges within an `AttributedString` are byte ranges into its UTF-8 text, and must fall on
+//! character boundaries.
+
+use draw::{Color, DrawContext};
+use ffi_tools;
+use std::collections::BTreeMap;
+use std::ffi::{CStr, CString};
+use std::fmt;
+use std::ops::Range;
+use std::os::raw::c_char;
+use ui::UI;
+use ui_sys::{
+    self, uiAttribute, uiAttributedString, uiDrawTextAlign, uiDrawTextLayout,
+    uiDrawTextLayoutParams, uiFontDescriptor, uiTextItalic, uiTextStretch, uiTextWeight,
+    uiUnderline, uiUnderlineColor,
+};
+
+/// The weight of a font, from `0` to `1000`. Common weights are provided as constants.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TextWeight(pub u32);
+
+impl TextWeight {
+    pub const MINIMUM: TextWeight = TextWeight(ui_sys::uiTextWeightMinimum as u32);
+    pub const THIN: TextWeight = TextWeight(ui_sys::uiTextWeightThin as u32);
+    pub const ULTRA_LIGHT: TextWeight = TextWeight(ui_sys::uiTextWeightUltraLight as u32);
+    pub const LIGHT: TextWeight = TextWeight(ui_sys::uiTextWeightLight as u32);
+    pub const BOOK: TextWeight = TextWeight(ui_sys::uiTextWeightBook as u32);
+    pub const NORMAL: TextWeight = TextWeight(ui_sys::uiTextWeightNormal as u32);
+    pub const MEDIUM: TextWeight = TextWeight(ui_sys::uiTextWeightMedium as u32);
+    pub const SEMI_BOLD: TextWeight = TextWeight(ui_sys::uiTextWeightSemiBold as u32);
+    pub const BOLD: TextWeight = TextWeight(ui_sys::uiTextWeightBold as u32);
+    pub const ULTRA_BOLD: TextWeight = TextWeight(ui_sys::uiTextWeightUltraBold as u32);
+    pub const HEAVY: TextWeight = TextWeight(ui_sys::uiTextWeightHeavy as u32);
+    pub const ULTRA_HEAVY: TextWeight = TextWeight(ui_sys::uiTextWeightUltraHeavy as u32);
+    pub const MAXIMUM: TextWeight = TextWeight(ui_sys::uiTextWeightMaximum as u32);
+
+    fn into_ui_text_weight(self) -> uiTextWeight {
+        self.0 as uiTextWeight
+    }
+}
+
+/// Whether a font is upright or slanted.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum TextItalic {
+    Normal,
+    /// Slanted by skewing the upright font.
+    Oblique,
+    /// Slanted using a true italic design.
+    Italic,
+}
+
+impl TextItalic {
+    fn into_ui_text_italic(self) -> uiTextItalic {
+        return match self {
+            TextItalic::Normal => ui_sys::uiTextItalicNormal,
+            TextItalic::Oblique => ui_sys::uiTextItalicOblique,
+            TextItalic::Italic => ui_sys::uiTextItalicItalic,
+        } as uiTextItalic;
+    }
+
+    fn from_ui_text_italic(italic: uiTextItalic) -> TextItalic {
+        if italic == ui_sys::uiTextItalicOblique as uiTextItalic {
+            TextItalic::Oblique
+        } else if italic == ui_sys::uiTextItalicItalic as uiTextItalic {
+            TextItalic::Italic
+        } else {
+            TextItalic::Normal
+        }
+    }
+}
+
+/// How narrow or wide a font is.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum TextStretch {
+    UltraCondensed,
+    ExtraCondensed,
+    Condensed,
+    SemiCondensed,
+    Normal,
+    SemiExpanded,
+    Expanded,
+    ExtraExpanded,
+    UltraExpanded,
+}
+
+const TEXT_STRETCHES: [TextStretch; 9] = [
+    TextStretch::UltraCondensed,
+    TextStretch::ExtraCondensed,
+    TextStretch::Condensed,
+    TextStretch::SemiCondensed,
+    TextStretch::Normal,
+    TextStretch::SemiExpanded,
+    TextStretch::Expanded,
+    TextStretch::ExtraExpanded,
+    TextStretch::UltraExpanded,
+];
+
+impl TextStretch {
+    fn into_ui_text_stretch(self) -> uiTextStretch {
+        return match self {
+            TextStretch::UltraCondensed => ui_sys::uiTextStretchUltraCondensed,
+            TextStretch::ExtraCondensed => ui_sys::uiTextStretchExtraCondensed,
+            TextStretch::Condensed => ui_sys::uiTextStretchCondensed,
+            TextStretch::SemiCondensed => ui_sys::uiTextStretchSemiCondensed,
+            TextStretch::Normal => ui_sys::uiTextStretchNormal,
+            TextStretch::SemiExpanded => ui_sys::uiTextStretchSemiExpanded,
+            TextStretch::Expanded => ui_sys::uiTextStretchExpanded,
+            TextStretch::ExtraExpanded => ui_sys::uiTextStretchExtraExpanded,
+            TextStretch::UltraExpanded => ui_sys::uiTextStretchUltraExpanded,
+        } as uiTextStretch;
+    }
+
+    fn from_ui_text_stretch(stretch: uiTextStretch) -> TextStretch {
+        TEXT_STRETCHES
+            .iter()
+            .cloned()
+            .find(|s| s.into_ui_text_stretch() == stretch)
+            .unwrap_or(TextStretch::Normal)
+    }
+}
+
+/// Describes a font: its family, size and style.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FontDescriptor {
+    /// The name of the font family, such as `"Helvetica"`.
+    pub family: String,
+    /// The size of the font, in points.
+    pub size: f64,
+    pub weight: TextWeight,
+    pub italic: TextItalic,
+    pub stretch: TextStretch,
+}
+
+impl FontDescriptor {
+    /// Create a new upright font descriptor of normal weight and width.
+    pub fn new(family: &str, size: f64) -> FontDescriptor {
+        FontDescriptor {
+            family: family.to_string(),
+            size,
+            weight: TextWeight::NORMAL,
+            italic: TextItalic::Normal,
+            stretch: TextStretch::Normal,
+        }
+    }
+
+    /// Copies a descriptor filled in by libui. The caller remains responsible for freeing it.
+    ///
+    /// # Unsafety
+    /// The descriptor's family must be a valid C string.
+    pub(crate) unsafe fn from_ui_font_descriptor(desc: &uiFontDescriptor) -> FontDescriptor {
+        FontDescriptor {
+            family: CStr::from_ptr(desc.Family).to_string_lossy().into_owned(),
+            size: desc.Size,
+            weight: TextWeight(desc.Weight as u32),
+            italic: TextItalic::from_ui_text_italic(desc.Italic),
+            stretch: TextStretch::from_ui_text_stretch(desc.Stretch),
+        }
+    }
+}
+
+/// The style of line drawn under text.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Underline {
+    None,
+    Single,
+    Double,
+    /// The wavy line used to mark spelling mistakes and the like.
+    Suggestion,
+}
+
+impl Underline {
+    fn into_ui_underline(self) -> uiUnderline {
+        return match self {
+            Underline::None => ui_sys::uiUnderlineNone,
+            Underline::Single => ui_sys::uiUnderlineSingle,
+            Underline::Double => ui_sys::uiUnderlineDouble,
+            Underline::Suggestion => ui_sys::uiUnderlineSuggestion,
+        } as uiUnderline;
+    }
+}
+
+/// The color of the line drawn under text.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum UnderlineColor {
+    /// The given color.
+    Custom(Color),
+    /// The color the platform uses to mark spelling mistakes.
+    Spelling,
+    /// The color the platform uses to mark grammatical mistakes.
+    Grammar,
+    /// The color the platform uses for other marks, such as in input methods.
+    Auxiliary,
+}
+
+/// A set of OpenType font features, such as ligatures or small capitals, each identified by
+/// its four-byte tag and set to a value. Most features are turned on with a value of `1`
+/// and off with `0`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct OpenTypeFeatures {
+    features: BTreeMap<[u8; 4], u32>,
+}
+
+impl OpenTypeFeatures {
+    /// Create an empty set of features.
+    pub fn new() -> OpenTypeFeatures {
+        OpenTypeFeatures {
+            features: BTreeMap::new(),
+        }
+    }
+
+    /// Set the given feature, such as `b"liga"`, to the given value.
+    pub fn add(&mut self, tag: &[u8; 4], value: u32) {
+        self.features.insert(*tag, value);
+    }
+
+    /// Remove the given feature from the set.
+    pub fn remove(&mut self, tag: &[u8; 4]) {
+        self.features.remove(tag);
+    }
+
+    /// Get the value of the given feature, if it is in the set.
+    pub fn get(&self, tag: &[u8; 4]) -> Option<u32> {
+        self.features.get(tag).cloned()
+    }
+
+    /// Iterate over the features in the set, in order of their tags.
+    pub fn iter<'a>(&'a self) -> impl Iterator<Item = (&'a [u8; 4], u32)> + 'a {
+        self.features.iter().map(|(tag, &value)| (tag, value))
+    }
+
+    fn new_ui_attribute(&self) -> *mut uiAttribute {
+        unsafe {
+            let otf = ui_sys::uiNewOpenTypeFeatures();
+            for (tag, &value) in &self.features {
+                ui_sys::uiOpenTypeFeaturesAdd(
+                    otf,
+                    tag[0] as c_char,
+                    tag[1] as c_char,
+                    tag[2] as c_char,
+                    tag[3] as c_char,
+                    value,
+                );
+            }
+            // The attribute keeps its own copy of the features.
+            let attribute = ui_sys::uiNewFeaturesAttribute(otf);
+            ui_sys::uiFreeOpenTypeFeatures(otf);
+            attribute
+        }
+    }
+}
+
+/// Styling which can be applied to a range of an `AttributedString`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum TextAttribute {
+    /// The font family, such as `"Helvetica"`.
+    Family(String),
+    /// The font size, in points.
+    Size(f64),
+    Weight(TextWeight),
+    Italic(TextItalic),
+    Stretch(TextStretch),
+    /// The color of the text itself.
+    Color(Color),
+    /// The color drawn behind the text.
+    Background(Color),
+    Underline(Underline),
+    UnderlineColor(UnderlineColor),
+    Features(OpenTypeFeatures),
+}
+
+impl TextAttribute {
+    /// Allocates a new `uiAttribute` holding this attribute. Ownership passes to the caller.
+    fn new_ui_attribute(&self) -> *mut uiAttribute {
+        unsafe {
+            match *self {
+                TextAttribute::Family(ref family) => {
+                    let c_string = CString::new(family.as_bytes().to_vec()).unwrap();
+                    ui_sys::uiNewFamilyAttribute(c_string.as_ptr())
+                }
+                TextAttribute::Size(size) => ui_sys::uiNewSizeAttribute(size),
+                TextAttribute::Weight(weight) => {
+                    ui_sys::uiNewWeightAttribute(weight.into_ui_text_weight())
+                }
+                TextAttribute::Italic(italic) => {
+                    ui_sys::uiNewItalicAttribute(italic.into_ui_text_italic())
+                }
+                TextAttribute::Stretch(stretch) => {
+                    ui_sys::uiNewStretchAttribute(stretch.into_ui_text_stretch())
+                }
+                TextAttribute::Color(c) => ui_sys::uiNewColorAttribute(c.r, c.g, c.b, c.a),
+                TextAttribute::Background(c) => {
+                    ui_sys::uiNewBackgroundAttribute(c.r, c.g, c.b, c.a)
+                }
+                TextAttribute::Underline(underline) => {
+                    ui_sys::uiNewUnderlineAttribute(underline.into_ui_underline())
+                }
+                TextAttribute::UnderlineColor(color) => {
+                    let (kind, c) = match color {
+                        UnderlineColor::Custom(c) => (ui_sys::uiUnderlineColorCustom, c),
+                        UnderlineColor::Spelling => (
+                            ui_sys::uiUnderlineColorSpelling,
+                            Color::new(0.0, 0.0, 0.0, 0.0),
+                        ),
+                        UnderlineColor::Grammar => (
+                            ui_sys::uiUnderlineColorGrammar,
+                            Color::new(0.0, 0.0, 0.0, 0.0),
+                        ),
+                        UnderlineColor::Auxiliary => (
+                            ui_sys::uiUnderlineColorAuxiliary,
+                            Color::new(0.0, 0.0, 0.0, 0.0),
+                        ),
+                    };
+                    ui_sys::uiNewUnderlineColorAttribute(
+                        kind as uiUnderlineColor,
+                        c.r,
+                        c.g,
+                        c.b,
+                        c.a,
+                    )
+                }
+                TextAttribute::Features(ref features) => features.new_ui_attribute(),
+            }
+        }
+    }
+}
+
+/// Text with styling applied to ranges of it, ready to be laid out with a `TextLayout`.
+///
+/// ```no_run
+/// # use iui::prelude::*;
+/// # use iui::draw::Color;
+/// # use iui::draw::text::{AttributedString, TextAttribute, TextWeight};
+/// let ui = UI::init().unwrap();
+/// let mut string = AttributedString::new(&ui, "Hello, ");
+/// string.append_with_attributes(
+///     "world",
+///     &[
+///         TextAttribute::Weight(TextWeight::BOLD),
+///         TextAttribute::Color(Color::new(0.8, 0.0, 0.0, 1.0)),
+///     ],
+/// );
+/// string.append_unattributed("!");
+/// ```
+pub struct AttributedString {
+    ui_attributed_string: *mut uiAttributedString,
+}
+
+impl Drop for AttributedString {
+    fn drop(&mut self) {
+        unsafe { ui_sys::uiFreeAttributedString(self.ui_attributed_string) }
+    }
+}
+
+impl fmt::Debug for AttributedString {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("AttributedString")
+            .field("text", &self.text())
+            .finish()
+    }
+}
+
+impl AttributedString {
+    /// Create a new string holding the given text, without any attributes.
+    pub fn new(_ctx: &UI, text: &str) -> AttributedString {
+        ffi_tools::assert_main_thread();
+        let c_string = CString::new(text.as_bytes().to_vec()).unwrap();
+        AttributedString {
+            ui_attributed_string: unsafe { ui_sys::uiNewAttributedString(c_string.as_ptr()) },
+        }
+    }
+
+    /// Get a copy of the text of the string.
+    pub fn text(&self) -> String {
+        unsafe {
+            CStr::from_ptr(ui_sys::uiAttributedStringString(self.ui_attributed_string))
+                .to_string_lossy()
+                .into_owned()
+        }
+    }
+
+    /// The length of the text, in bytes.
+    pub fn len(&self) -> usize {
+        unsafe { ui_sys::uiAttributedStringLen(self.ui_attributed_string) as usize }
+    }
+
+    /// Returns `true` if the string holds no text.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Add text to the end of the string, without any attributes.
+    pub fn append_unattributed(&mut self, text: &str) {
+        let c_string = CString::new(text.as_bytes().to_vec()).unwrap();
+        unsafe {
+            ui_sys::uiAttributedStringAppendUnattributed(
+                self.ui_attributed_string,
+                c_string.as_ptr(),
+            )
+        }
+    }
+
+    /// Add text to the end of the string, with the given attributes applied to it.
+    pub fn append_with_attributes(&mut self, text: &str, attributes: &[TextAttribute]) {
+        let start = self.len();
+        self.append_unattributed(text);
+        let end = self.len();
+        for attribute in attributes {
+            self.set_attribute(attribute, start..end);
+        }
+    }
+
+    /// Insert text at the given byte index, without any attributes.
+    ///
+    /// # Panics
+    /// Panics if `at` is past the end of the string or not on a character boundary.
+    pub fn insert_at_unattributed(&mut self, text: &str, at: usize) {
+        self.check_range(at..at);
+        let c_string = CString::new(text.as_bytes().to_vec()).unwrap();
+        unsafe {
+            ui_sys::uiAttributedStringInsertAtUnattributed(
+                self.ui_attributed_string,
+                c_string.as_ptr(),
+                at as _,
+            )
+        }
+    }
+
+    /// Remove the given byte range of text, along with its attributes.
+    ///
+    /// # Panics
+    /// Panics if the range is out of bounds or not on character boundaries.
+    pub fn delete(&mut self, range: Range<usize>) {
+        self.check_range(range.clone());
+        unsafe {
+            ui_sys::uiAttributedStringDelete(
+                self.ui_attributed_string,
+                range.start as _,
+                range.end as _,
+            )
+        }
+    }
+
+    /// Apply an attribute to the given byte range of text, replacing any attribute of the
+    /// same kind already applied there.
+    ///
+    /// # Panics
+    /// Panics if the range is out of bounds or not on character boundaries.
+    pub fn set_attribute(&mut self, attribute: &TextAttribute, range: Range<usize>) {
+        self.check_range(range.clone());
+        unsafe {
+            // The string takes ownership of the attribute.
+            ui_sys::uiAttributedStringSetAttribute(
+                self.ui_attributed_string,
+                attribute.new_ui_attribute(),
+                range.start as _,
+                range.end as _,
+            )
+        }
+    }
+
+    fn check_range(&self, range: Range<usize>) {
+        let len = self.len();
+        assert!(
+            range.start <= range.end
+                && range.end <= len
+                && self.is_char_boundary(range.start, len)
+                && self.is_char_boundary(range.end, len),
+            "invalid range {:?} for attributed string of length {}",
+            range,
+            len
+        );
+    }
+
+    /// Whether the given byte offset, which must be at most `len`, falls between characters.
+    /// Only the byte at the offset is read, so that the text need not be copied.
+    fn is_char_boundary(&self, index: usize, len: usize) -> bool {
+        if index == len {
+            return true;
+        }
+        let byte = unsafe {
+            *(ui_sys::uiAttributedStringString(self.ui_attributed_string) as *const u8).add(index)
+        };
+        // Every byte of UTF-8 text except continuation bytes, 0b10xxxxxx, starts a character.
+        byte & 0xC0 != 0x80
+    }
+
+    /// Return the underlying pointer for this string.
+    pub fn ptr(&self) -> *mut uiAttributedString {
+        self.ui_attributed_string
+    }
+}
+
+/// How the lines of a `TextLayout` are aligned within its width.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum TextAlign {
+    Left,
+    Center,
+    Right,
+}
+
+impl TextAlign {
+    fn into_ui_draw_text_align(self) -> uiDrawTextAlign {
+        return match self {
+            TextAlign::Left => ui_sys::uiDrawTextAlignLeft,
+            TextAlign::Center => ui_sys::uiDrawTextAlignCenter,
+            TextAlign::Right => ui_sys::uiDrawTextAlignRight,
+        } as uiDrawTextAlign;
+    }
+}
+
+/// An `AttributedString` laid out into lines, which can be drawn with
+/// `DrawContext::draw_text`.
+pub struct TextLayout {
+    ui_draw_text_layout: *mut uiDrawTextLayout,
+}
+
+impl Drop for TextLayout {
+    fn drop(&mut self) {
+        unsafe { ui_sys::uiDrawFreeTextLayout(self.ui_draw_text_layout) }
+    }
+}
+
+impl TextLayout {
+    /// Lay out the given string, using `default_font` for any text without font attributes.
+    /// Lines are wrapped to fit within `width` and aligned within it; if `width` is `None`,
+    /// lines are never wrapped.
+    ///
+    /// The layout copies what it needs, so the string may be changed or dropped afterwards.
+    pub fn new(
+        _ctx: &DrawContext,
+        string: &AttributedString,
+        default_font: &FontDescriptor,
+        width: Option<f64>,
+        align: TextAlign,
+    ) -> TextLayout {
+        let family = CString::new(default_font.family.as_bytes().to_vec()).unwrap();
+        let mut font = uiFontDescriptor {
+            Family: family.as_ptr() as *mut c_char,
+            Size: default_font.size,
+            Weight: default_font.weight.into_ui_text_weight(),
+            Italic: default_font.italic.into_ui_text_italic(),
+            Stretch: default_font.stretch.into_ui_text_stretch(),
+        };
+        let mut params = uiDrawTextLayoutParams {
+            String: string.ptr(),
+            DefaultFont: &mut font,
+            Width: width.unwrap_or(-1.0),
+            Align: align.into_ui_draw_text_align(),
+        };
+        TextLayout {
+            ui_draw_text_layout: unsafe { ui_sys::uiDrawNewTextLayout(&mut params) },
+        }
+    }
+
+    /// The width and height of the laid out text.
+    pub fn extents(&self) -> (f64, f64) {
+        let mut extents = (0.0, 0.0);
+        unsafe {
+            ui_sys::uiDrawTextLayoutExtents(
+                self.ui_draw_text_layout,
+                &mut extents.0,
+                &mut extents.1,
+            )
+        }
+        extents
+    }
+
+    /// Return the underlying pointer for this layout.
+    pub fn ptr(&self) -> *mut uiDrawTextLayout {
+        self.ui_draw_text_layout
+    }
+}
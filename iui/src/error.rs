@@ -1,5 +1,7 @@
 //! Error types for this crate.
 
+use controls::TableValueType;
+
 /// The error type returned by functions in this crate which might fail.
 #[derive(Fail, Debug)]
 pub enum UIError {
@@ -13,10 +15,60 @@ pub enum UIError {
     /// one already existed.
     #[fail(display = "cannot initialize multiple instances of the libui toolkit")]
     MultipleInitError(),
+    /// Signifies that an attempt was made to queue work on the UI thread after the UI shut down.
+    #[fail(display = "cannot queue work on the UI thread: the UI has shut down")]
+    ShutDownError(),
     /// Signifies that an attempt was made to remove a tab from a tab group that was out of bounds.
     #[fail(
         display = "cannot remove index {} from tab group: there are only {} tabs in the group",
         index, n
     )]
     TabGroupIndexOutOfBounds { index: i32, n: i32 },
+    /// Signifies that an attempt was made to remove a control from a box that was out of bounds.
+    #[fail(
+        display = "cannot remove index {} from box: there are only {} controls in the box",
+        index, n
+    )]
+    BoxIndexOutOfBounds { index: i32, n: i32 },
+    /// Signifies that an attempt was made to remove a control from a form that was out of bounds.
+    #[fail(
+        display = "cannot remove index {} from form: there are only {} controls in the form",
+        index, n
+    )]
+    FormIndexOutOfBounds { index: i32, n: i32 },
+    /// Signifies that an attempt was made to begin moving or resizing a window from an `Area`
+    /// other than while handling a mouse button press on it.
+    #[fail(
+        display = "cannot move or resize the window: the area is not handling a mouse button press"
+    )]
+    NoMouseDownError(),
+    /// Signifies that image data could not be decoded.
+    #[fail(display = "unable to decode image: {}", error)]
+    ImageDecodeError { error: String },
+    /// Signifies that the user edited a table cell in a column which is not editable.
+    #[fail(
+        display = "cannot edit cell in row {}, column {}: the column is not editable",
+        row, column
+    )]
+    TableCellNotEditable { row: i32, column: i32 },
+    /// Signifies that a table cell edit carried a value of the wrong type for its column.
+    #[fail(
+        display = "cannot edit cell in row {}, column {}: expected a value of type {:?}",
+        row, column, expected
+    )]
+    TableCellTypeMismatch {
+        row: i32,
+        column: i32,
+        expected: TableValueType,
+    },
+    /// Signifies that a table cell edit was rejected by the model's validation.
+    #[fail(
+        display = "cannot edit cell in row {}, column {}: {}",
+        row, column, reason
+    )]
+    TableEditRejected {
+        row: i32,
+        column: i32,
+        reason: String,
+    },
 }
@@ -1,9 +1,18 @@
 //! Utilities to manage the state of the interface to the libUI bindings.
+use std::cell::Cell;
 use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
 
 static INITIALIZED: AtomicBool = AtomicBool::new(false);
 
-/// Set the global flag stating that libUI is initialized.
+thread_local! {
+    /// Whether this is the thread which initialized libUI, which is the only thread that may
+    /// call into it.
+    static MAIN_THREAD: Cell<bool> = Cell::new(false)
+}
+
+/// Set the global flag stating that libUI is initialized, recording the calling thread as the
+/// thread which owns it.
 ///
 /// # Unsafety
 /// If this is called when the library is not, in fact, initialized,
@@ -14,6 +23,7 @@ static INITIALIZED: AtomicBool = AtomicBool::new(false);
 pub unsafe fn set_initialized() {
     assert!(!INITIALIZED.swap(true, Ordering::SeqCst),
         "Tried to initialize libUI when it was already initialized. Aborting because this is an unsafe situation.");
+    MAIN_THREAD.with(|main| main.set(true));
 }
 
 /// Set the global flag stating that libUI is no longer initialized.
@@ -24,9 +34,64 @@ pub unsafe fn set_initialized() {
 /// invariants and likely causing a segfault.
 pub unsafe fn unset_initialized() {
     INITIALIZED.store(false, Ordering::SeqCst);
+    MAIN_THREAD.with(|main| main.set(false));
 }
 
 /// Retrieve the global flag indicating whether libUI is initialized.
 pub fn is_initialized() -> bool {
     INITIALIZED.load(Ordering::SeqCst)
 }
+
+/// Retrieve whether the calling thread is the one which initialized libUI.
+pub fn is_main_thread() -> bool {
+    MAIN_THREAD.with(Cell::get)
+}
+
+/// Check that the calling thread may call into libUI: that is, that libUI is not initialized,
+/// or that this is the thread which initialized it.
+///
+/// This is a debug assertion, unless the `enforce-main-thread` feature is enabled, in which
+/// case it is always checked.
+///
+/// # Panics
+/// Panics if libUI is initialized and this is called from any other thread.
+#[inline]
+pub fn assert_main_thread() {
+    if cfg!(any(debug_assertions, feature = "enforce-main-thread"))
+        && is_initialized()
+        && !is_main_thread()
+    {
+        wrong_thread()
+    }
+}
+
+#[cold]
+fn wrong_thread() -> ! {
+    let current = thread::current();
+    panic!(
+        "libUI was used from thread {}, but may only be used from the thread which called UI::init",
+        current
+            .name()
+            .map_or_else(|| format!("{:?}", current.id()), |name| format!("'{}'", name))
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn other_threads_are_not_the_main_thread() {
+        MAIN_THREAD.with(|main| main.set(true));
+        assert!(is_main_thread());
+        assert!(!thread::spawn(is_main_thread).join().unwrap());
+        MAIN_THREAD.with(|main| main.set(false));
+        assert!(!is_main_thread());
+    }
+
+    #[test]
+    #[should_panic(expected = "may only be used from the thread which called UI::init")]
+    fn wrong_thread_panics() {
+        wrong_thread()
+    }
+}
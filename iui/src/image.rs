@@ -0,0 +1,257 @@
+//! Images which can be displayed by controls, such as in the image columns of a `Table`.
+
+#[cfg(feature = "png")]
+use error::UIError;
+use ffi_tools;
+#[cfg(feature = "png")]
+use png;
+use std::fmt;
+use std::os::raw::{c_int, c_void};
+use ui::UI;
+use ui_sys::{self, uiImage};
+
+/// A bitmap image, owned by Rust and freed when dropped.
+///
+/// An `Image` has a size in points; the pixel data backing it is added with
+/// [`append`](#method.append). Controls which display an image pick the pixel data most
+/// appropriate for the current display, so an image meant to look sharp on high-DPI displays
+/// should be given several representations: for instance, a 16x16 point icon could be given
+/// both 16x16 and 32x32 pixel versions.
+///
+/// ```no_run
+/// # use iui::prelude::*;
+/// # use iui::Image;
+/// let ui = UI::init().unwrap();
+/// let mut icon = Image::from_png(&ui, &std::fs::read("icon.png").unwrap()).unwrap();
+/// icon.append_png(&ui, &std::fs::read("icon@2x.png").unwrap()).unwrap();
+/// ```
+pub struct Image {
+    ui_image: *mut uiImage,
+}
+
+impl Drop for Image {
+    fn drop(&mut self) {
+        unsafe { ui_sys::uiFreeImage(self.ui_image) }
+    }
+}
+
+impl fmt::Debug for Image {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Image")
+            .field("ui_image", &self.ui_image)
+            .finish()
+    }
+}
+
+impl Image {
+    /// Create a new, empty image with the given size in points.
+    pub fn new(_ctx: &UI, width: f64, height: f64) -> Image {
+        ffi_tools::assert_main_thread();
+        Image {
+            ui_image: unsafe { ui_sys::uiNewImage(width, height) },
+        }
+    }
+
+    /// Add pixel data to the image. `pixels` is a buffer of 8-bit RGBA pixels, laid out in rows
+    /// of `byte_stride` bytes each. The data is copied, so the buffer may be reused afterwards.
+    ///
+    /// # Panics
+    /// Panics if `pixels` is too small to hold `pixel_height` rows of `pixel_width` pixels
+    /// at the given stride, or if the dimensions are negative or too large to address.
+    pub fn append(
+        &mut self,
+        _ctx: &UI,
+        pixels: &[u8],
+        pixel_width: i32,
+        pixel_height: i32,
+        byte_stride: i32,
+    ) {
+        let required = match buffer_len(pixel_width, pixel_height, byte_stride) {
+            Some(required) => required,
+            None => panic!(
+                "invalid image dimensions {}x{} with stride {}",
+                pixel_width, pixel_height, byte_stride
+            ),
+        };
+        assert!(
+            pixels.len() >= required,
+            "pixel buffer of {} bytes is too small for {} rows of {} bytes",
+            pixels.len(),
+            pixel_height,
+            byte_stride
+        );
+        unsafe {
+            ui_sys::uiImageAppend(
+                self.ui_image,
+                pixels.as_ptr() as *mut c_void,
+                pixel_width as c_int,
+                pixel_height as c_int,
+                byte_stride as c_int,
+            )
+        }
+    }
+
+    /// Create a new image from PNG data, with a size in points equal to its size in pixels.
+    #[cfg(feature = "png")]
+    pub fn from_png(ctx: &UI, png_data: &[u8]) -> Result<Image, UIError> {
+        let decoded = decode_png(png_data)?;
+        let mut image = Image::new(ctx, decoded.width as f64, decoded.height as f64);
+        image.append_decoded(ctx, &decoded)?;
+        Ok(image)
+    }
+
+    /// Add pixel data decoded from PNG data to the image, as with `append`.
+    #[cfg(feature = "png")]
+    pub fn append_png(&mut self, ctx: &UI, png_data: &[u8]) -> Result<(), UIError> {
+        let decoded = decode_png(png_data)?;
+        self.append_decoded(ctx, &decoded)
+    }
+
+    #[cfg(feature = "png")]
+    fn append_decoded(&mut self, ctx: &UI, decoded: &DecodedPng) -> Result<(), UIError> {
+        let (width, height) = (decoded.width as i32, decoded.height as i32);
+        let stride = width.wrapping_mul(4);
+        if width < 0 || height < 0 || stride / 4 != width {
+            return Err(UIError::ImageDecodeError {
+                error: format!(
+                    "image of {}x{} pixels is too large",
+                    decoded.width, decoded.height
+                ),
+            });
+        }
+        self.append(ctx, &decoded.pixels, width, height, stride);
+        Ok(())
+    }
+
+    /// Create a fully transparent image of a single point and pixel, for places which must show
+    /// an image but have none.
+    pub(crate) fn transparent_pixel() -> Image {
+        let pixel = [0u8; 4];
+        unsafe {
+            let ui_image = ui_sys::uiNewImage(1.0, 1.0);
+            ui_sys::uiImageAppend(ui_image, pixel.as_ptr() as *mut c_void, 1, 1, 4);
+            Image { ui_image }
+        }
+    }
+
+    /// Return the underlying pointer for this image.
+    pub fn ptr(&self) -> *mut uiImage {
+        self.ui_image
+    }
+}
+
+/// The number of bytes needed to hold `pixel_height` rows of `byte_stride` bytes, each of
+/// which must fit `pixel_width` RGBA pixels, or `None` if the dimensions are invalid or the
+/// size overflows.
+fn buffer_len(pixel_width: i32, pixel_height: i32, byte_stride: i32) -> Option<usize> {
+    if pixel_width < 0 || pixel_height < 0 || byte_stride < 0 {
+        return None;
+    }
+    let row_len = (pixel_width as usize).checked_mul(4)?;
+    if (byte_stride as usize) < row_len {
+        return None;
+    }
+    (byte_stride as usize).checked_mul(pixel_height as usize)
+}
+
+/// An image decoded from PNG data into tightly packed 8-bit RGBA pixels.
+#[cfg(feature = "png")]
+struct DecodedPng {
+    pixels: Vec<u8>,
+    width: u32,
+    height: u32,
+}
+
+#[cfg(feature = "png")]
+fn decode_png(png_data: &[u8]) -> Result<DecodedPng, UIError> {
+    let decode_error = |error: png::DecodingError| UIError::ImageDecodeError {
+        error: error.to_string(),
+    };
+
+    let mut decoder = png::Decoder::new(png_data);
+    decoder.set_transformations(png::Transformations::EXPAND | png::Transformations::STRIP_16);
+    let (info, mut reader) = decoder.read_info().map_err(decode_error)?;
+    let mut buffer = vec![0; info.buffer_size()];
+    reader.next_frame(&mut buffer).map_err(decode_error)?;
+
+    let pixel_count = info.width as usize * info.height as usize;
+    let mut pixels = Vec::with_capacity(pixel_count * 4);
+    match info.color_type {
+        png::ColorType::RGBA => pixels = buffer,
+        png::ColorType::RGB => {
+            for rgb in buffer.chunks(3) {
+                pixels.extend_from_slice(&[rgb[0], rgb[1], rgb[2], 255]);
+            }
+        }
+        png::ColorType::GrayscaleAlpha => {
+            for ga in buffer.chunks(2) {
+                pixels.extend_from_slice(&[ga[0], ga[0], ga[0], ga[1]]);
+            }
+        }
+        png::ColorType::Grayscale => {
+            for &g in &buffer {
+                pixels.extend_from_slice(&[g, g, g, 255]);
+            }
+        }
+        png::ColorType::Indexed => {
+            return Err(UIError::ImageDecodeError {
+                error: "indexed PNG data was not expanded".to_string(),
+            })
+        }
+    }
+    pixels.truncate(pixel_count * 4);
+
+    Ok(DecodedPng {
+        pixels,
+        width: info.width,
+        height: info.height,
+    })
+}
+
+#[cfg(all(test, feature = "png"))]
+mod tests {
+    use super::*;
+
+    fn encode(width: u32, height: u32, color_type: png::ColorType, data: &[u8]) -> Vec<u8> {
+        let mut png_data = Vec::new();
+        {
+            let mut encoder = png::Encoder::new(&mut png_data, width, height);
+            encoder.set_color(color_type);
+            encoder.set_depth(png::BitDepth::Eight);
+            let mut writer = encoder.write_header().unwrap();
+            writer.write_image_data(data).unwrap();
+        }
+        png_data
+    }
+
+    #[test]
+    fn decodes_to_rgba() {
+        let rgb = encode(2, 1, png::ColorType::RGB, &[255, 0, 0, 0, 0, 255]);
+        let decoded = decode_png(&rgb).unwrap();
+        assert_eq!((decoded.width, decoded.height), (2, 1));
+        assert_eq!(decoded.pixels, vec![255, 0, 0, 255, 0, 0, 255, 255]);
+
+        let gray = encode(1, 1, png::ColorType::GrayscaleAlpha, &[7, 128]);
+        assert_eq!(decode_png(&gray).unwrap().pixels, vec![7, 7, 7, 128]);
+    }
+
+    #[test]
+    fn buffer_len_rejects_overflow() {
+        assert_eq!(buffer_len(2, 3, 8), Some(24));
+        assert_eq!(buffer_len(2, 3, 7), None);
+        assert_eq!(buffer_len(-1, 3, 8), None);
+        assert_eq!(buffer_len(0x4000_0000, 1, i32::max_value()), None);
+        assert_eq!(
+            buffer_len(1, i32::max_value(), i32::max_value()),
+            (i32::max_value() as usize).checked_mul(i32::max_value() as usize)
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_data() {
+        match decode_png(b"not a png") {
+            Err(UIError::ImageDecodeError { .. }) => {}
+            _ => panic!("invalid PNG data was decoded"),
+        }
+    }
+}
@@ -22,6 +22,8 @@ extern crate bitflags;
 #[macro_use]
 extern crate failure;
 extern crate libc;
+#[cfg(feature = "png")]
+extern crate png;
 extern crate ui_sys;
 
 mod callback_helpers;
@@ -30,12 +32,14 @@ pub mod controls;
 pub mod draw;
 mod error;
 mod ffi_tools;
+mod image;
 pub mod menus;
 pub mod str_tools;
 mod ui;
 
 pub use error::UIError;
-pub use ui::{EventLoop, UI};
+pub use image::Image;
+pub use ui::{EventLoop, UIHandle, UISender, UI};
 
 /// Common imports are packaged into this module. It's meant to be glob-imported: `use iui::prelude::*`.
 pub mod prelude {
@@ -1,17 +1,40 @@
 //! Menus that appear at the top of windows, and the items that go in them.
 
-use callback_helpers::{from_void_ptr, to_heap_ptr};
+use callback_helpers::{from_void_ptr, register_callback};
 use controls::Window;
+use ffi_tools;
+use std::cell::RefCell;
+use std::collections::HashSet;
 use std::ffi::CString;
 use std::os::raw::{c_int, c_void};
 use ui_sys::{self, uiMenu, uiMenuItem, uiWindow};
 use UI;
 
+thread_local! {
+    /// The standard menu items which have been added. The UI library aborts the whole process
+    /// if any of them is added twice, so this is checked beforehand.
+    static STANDARD_ITEMS: RefCell<HashSet<&'static str>> = RefCell::new(HashSet::new())
+}
+
+/// Record that the standard item of the given name is being added.
+///
+/// # Panics
+/// Panics if the item has already been added.
+fn claim_standard_item(name: &'static str) {
+    let first = STANDARD_ITEMS.with(|items| items.borrow_mut().insert(name));
+    assert!(
+        first,
+        "only one {} menu item may be added to an application",
+        name
+    );
+}
+
 /// A `MenuItem` represents an item that is shown in a `Menu`. Note that, unlike many controls,
 /// the text on `MenuItem`s cannot be changed after creation.
 #[derive(Clone)]
 pub struct MenuItem {
     ui_menu_item: *mut uiMenuItem,
+    is_quit_item: bool,
 }
 
 /// A `Menu` represents one of the top-level menus at the top of a window. As that bar is unique
@@ -46,10 +69,19 @@ impl MenuItem {
     }
 
     /// Sets the function to be executed when the item is clicked/selected.
+    ///
+    /// # Panics
+    /// Panics if this is the item added by `Menu::append_quit_item`, which can only be handled
+    /// through `UI::on_should_quit`.
     pub fn on_clicked<'ctx, F>(&self, _ctx: &'ctx UI, callback: F)
     where
         F: FnMut(&MenuItem, &Window) + 'static,
     {
+        assert!(
+            !self.is_quit_item,
+            "the quit menu item is handled through UI::on_should_quit"
+        );
+
         extern "C" fn c_callback<G: FnMut(&MenuItem, &Window)>(
             menu_item: *mut uiMenuItem,
             window: *mut uiWindow,
@@ -65,20 +97,25 @@ impl MenuItem {
             ui_sys::uiMenuItemOnClicked(
                 self.ui_menu_item,
                 Some(c_callback::<F>),
-                to_heap_ptr(callback),
+                register_callback(self.ui_menu_item as *mut c_void, "on_clicked", callback),
             );
         }
     }
 
     // Creates a `MenuItem` from a raw pointer
     pub unsafe fn from_raw(raw: *mut uiMenuItem) -> Self {
-        MenuItem { ui_menu_item: raw }
+        ffi_tools::assert_main_thread();
+        MenuItem {
+            ui_menu_item: raw,
+            is_quit_item: false,
+        }
     }
 }
 
 impl Menu {
     /// Creates a new menu with the given name to be displayed in the menubar at the top of the window.
     pub fn new(_ctx: &UI, name: &str) -> Menu {
+        ffi_tools::assert_main_thread();
         unsafe {
             let c_string = CString::new(name.as_bytes().to_vec()).unwrap();
             Menu {
@@ -91,9 +128,7 @@ impl Menu {
     pub fn append_item(&self, name: &str) -> MenuItem {
         unsafe {
             let c_string = CString::new(name.as_bytes().to_vec()).unwrap();
-            MenuItem {
-                ui_menu_item: ui_sys::uiMenuAppendItem(self.ui_menu, c_string.as_ptr()),
-            }
+            MenuItem::from_raw(ui_sys::uiMenuAppendItem(self.ui_menu, c_string.as_ptr()))
         }
     }
 
@@ -101,14 +136,66 @@ impl Menu {
     pub fn append_check_item(&self, name: &str) -> MenuItem {
         unsafe {
             let c_string = CString::new(name.as_bytes().to_vec()).unwrap();
-            MenuItem {
-                ui_menu_item: ui_sys::uiMenuAppendCheckItem(self.ui_menu, c_string.as_ptr()),
-            }
+            MenuItem::from_raw(ui_sys::uiMenuAppendCheckItem(
+                self.ui_menu,
+                c_string.as_ptr(),
+            ))
+        }
+    }
+
+    /// Adds the standard item for quitting the application, named and placed as is usual on
+    /// each platform. On macOS it appears in the application menu rather than in this one.
+    ///
+    /// Clicking it runs the callback set with `UI::on_should_quit`, which decides whether the
+    /// application quits.
+    ///
+    /// # Panics
+    /// Panics if a quit item has already been added; only one may be added to an application.
+    pub fn append_quit_item(&self) -> MenuItem {
+        claim_standard_item("quit");
+        MenuItem {
+            ui_menu_item: unsafe { ui_sys::uiMenuAppendQuitItem(self.ui_menu) },
+            is_quit_item: true,
         }
     }
 
+    /// Adds the standard item for opening the application's preferences, named and placed as
+    /// is usual on each platform.
+    ///
+    /// # Panics
+    /// Panics if a preferences item has already been added; only one may be added to an
+    /// application.
+    pub fn append_preferences_item(&self) -> MenuItem {
+        claim_standard_item("preferences");
+        unsafe { MenuItem::from_raw(ui_sys::uiMenuAppendPreferencesItem(self.ui_menu)) }
+    }
+
+    /// Adds the standard item for showing information about the application, named and
+    /// placed as is usual on each platform.
+    ///
+    /// # Panics
+    /// Panics if an about item has already been added; only one may be added to an
+    /// application.
+    pub fn append_about_item(&self) -> MenuItem {
+        claim_standard_item("about");
+        unsafe { MenuItem::from_raw(ui_sys::uiMenuAppendAboutItem(self.ui_menu)) }
+    }
+
     /// Adds a seperator to the menu.
     pub fn append_separator(&self) {
         unsafe { ui_sys::uiMenuAppendSeparator(self.ui_menu) }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[should_panic(expected = "only one about menu item")]
+    fn standard_items_can_only_be_added_once() {
+        claim_standard_item("quit");
+        claim_standard_item("about");
+        claim_standard_item("about");
+    }
+}
@@ -1,7 +1,10 @@
 //! Tools for making platform-independent string handling work properly
 
+use draw::text::AttributedString;
 use std::ffi::{CStr, CString};
+use std::ops::Range;
 use std::os::raw::c_char;
+use ui_sys;
 
 /// Replaces every occurrance of `"\r\n"` with a single newline `\n`, without collapsing
 /// newlines.
@@ -45,6 +48,230 @@ pub unsafe fn from_toolkit_string(c: *mut c_char) -> String {
     CStr::from_ptr(c).to_string_lossy().into_owned()
 }
 
+/// The boundaries between the grapheme clusters of an `AttributedString`, that is, between
+/// the characters as the user sees them, as reported by the platform.
+///
+/// A grapheme may be made of several `char`s, such as a letter followed by combining accents
+/// or an emoji with skin tone modifiers. Moving a cursor or deleting text one grapheme at a
+/// time, rather than one `char` at a time, ensures these are never split apart.
+///
+/// Boundaries are byte indices into the text of the string. They are a snapshot, so they
+/// must be fetched again after the string is changed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GraphemeBoundaries {
+    /// The byte index at which each grapheme starts, followed by the length of the text.
+    bounds: Vec<usize>,
+}
+
+impl GraphemeBoundaries {
+    /// Fetch the grapheme boundaries of the given string.
+    pub fn of(string: &AttributedString) -> GraphemeBoundaries {
+        let ptr = string.ptr();
+        unsafe {
+            let num_graphemes = ui_sys::uiAttributedStringNumGraphemes(ptr) as usize;
+            let mut bounds: Vec<usize> = (0..num_graphemes)
+                .map(|grapheme| {
+                    ui_sys::uiAttributedStringGraphemeToByteIndex(ptr, grapheme as _) as usize
+                })
+                .collect();
+            bounds.push(string.len());
+            GraphemeBoundaries { bounds }
+        }
+    }
+
+    /// The number of graphemes in the string.
+    pub fn num_graphemes(&self) -> usize {
+        self.bounds.len() - 1
+    }
+
+    /// The length of the string, in bytes.
+    pub fn byte_len(&self) -> usize {
+        self.bounds[self.bounds.len() - 1]
+    }
+
+    /// Returns `true` if the given byte index is the start or end of a grapheme.
+    pub fn is_boundary(&self, byte: usize) -> bool {
+        self.bounds.binary_search(&byte).is_ok()
+    }
+
+    /// The index of the grapheme containing the given byte, or the number of graphemes if the
+    /// byte index is at or past the end of the string.
+    pub fn byte_to_grapheme(&self, byte: usize) -> usize {
+        match self.bounds.binary_search(&byte) {
+            Ok(grapheme) => grapheme,
+            Err(after) => after - 1,
+        }
+    }
+
+    /// The byte index at which the given grapheme starts, or the length of the string if the
+    /// grapheme index is at or past the end of the string.
+    pub fn grapheme_to_byte(&self, grapheme: usize) -> usize {
+        match self.bounds.get(grapheme) {
+            Some(&byte) => byte,
+            None => self.byte_len(),
+        }
+    }
+
+    /// The byte range of the given grapheme, if it exists.
+    pub fn grapheme_range(&self, grapheme: usize) -> Option<Range<usize>> {
+        if grapheme < self.num_graphemes() {
+            Some(self.bounds[grapheme]..self.bounds[grapheme + 1])
+        } else {
+            None
+        }
+    }
+
+    /// The nearest boundary at or before the given byte index.
+    pub fn floor(&self, byte: usize) -> usize {
+        self.grapheme_to_byte(self.byte_to_grapheme(byte))
+    }
+
+    /// The boundary after the given byte index, if there is one.
+    pub fn next(&self, byte: usize) -> Option<usize> {
+        match self.bounds.binary_search(&byte) {
+            Ok(grapheme) => self.bounds.get(grapheme + 1).cloned(),
+            Err(after) => self.bounds.get(after).cloned(),
+        }
+    }
+
+    /// The boundary before the given byte index, if there is one.
+    pub fn prev(&self, byte: usize) -> Option<usize> {
+        match self.bounds.binary_search(&byte) {
+            Ok(0) | Err(0) => None,
+            Ok(grapheme) | Err(grapheme) => Some(self.bounds[grapheme - 1]),
+        }
+    }
+}
+
+/// A text cursor which moves over an `AttributedString` one grapheme at a time, and inserts
+/// and deletes text at its position.
+///
+/// The position is a byte index into the text, which is kept on a grapheme boundary by every
+/// method taking the current `GraphemeBoundaries` of the string.
+///
+/// ```no_run
+/// # use iui::prelude::*;
+/// # use iui::draw::text::AttributedString;
+/// # use iui::str_tools::{GraphemeBoundaries, GraphemeCursor};
+/// let ui = UI::init().unwrap();
+/// let mut string = AttributedString::new(&ui, "cafe\u{301}");
+/// let mut cursor = GraphemeCursor::new();
+/// cursor.move_to_end(&GraphemeBoundaries::of(&string));
+///
+/// // Removes both the "e" and its combining accent.
+/// let bounds = GraphemeBoundaries::of(&string);
+/// cursor.delete_backward(&mut string, &bounds);
+/// assert_eq!(string.text(), "caf");
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct GraphemeCursor {
+    position: usize,
+}
+
+impl GraphemeCursor {
+    /// Create a cursor at the start of the text.
+    pub fn new() -> GraphemeCursor {
+        GraphemeCursor { position: 0 }
+    }
+
+    /// The byte index of the cursor.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// The index of the grapheme the cursor is before.
+    pub fn grapheme(&self, bounds: &GraphemeBoundaries) -> usize {
+        bounds.byte_to_grapheme(self.position)
+    }
+
+    /// Move the cursor to the given byte index, or to the start of the grapheme containing it.
+    pub fn set_position(&mut self, bounds: &GraphemeBoundaries, byte: usize) {
+        self.position = bounds.floor(byte);
+    }
+
+    /// Move the cursor before the given grapheme, or to the end of the text.
+    pub fn set_grapheme(&mut self, bounds: &GraphemeBoundaries, grapheme: usize) {
+        self.position = bounds.grapheme_to_byte(grapheme);
+    }
+
+    /// Move the cursor to the start of the text.
+    pub fn move_to_start(&mut self) {
+        self.position = 0;
+    }
+
+    /// Move the cursor to the end of the text.
+    pub fn move_to_end(&mut self, bounds: &GraphemeBoundaries) {
+        self.position = bounds.byte_len();
+    }
+
+    /// Move the cursor forward one grapheme, returning `false` if it was already at the end.
+    pub fn move_next(&mut self, bounds: &GraphemeBoundaries) -> bool {
+        match bounds.next(self.position) {
+            Some(next) => {
+                self.position = next;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Move the cursor back one grapheme, returning `false` if it was already at the start.
+    pub fn move_prev(&mut self, bounds: &GraphemeBoundaries) -> bool {
+        match bounds.prev(self.position) {
+            Some(prev) => {
+                self.position = prev;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Insert text at the cursor, without any attributes, and move the cursor after it.
+    ///
+    /// Text beginning with combining characters joins the grapheme before the cursor, so the
+    /// boundaries of the string should be fetched again before the cursor is next used.
+    pub fn insert(&mut self, string: &mut AttributedString, text: &str) {
+        string.insert_at_unattributed(text, self.position);
+        self.position += text.len();
+    }
+
+    /// Delete the grapheme before the cursor, as with the backspace key, returning `false` if
+    /// the cursor was at the start of the text.
+    pub fn delete_backward(
+        &mut self,
+        string: &mut AttributedString,
+        bounds: &GraphemeBoundaries,
+    ) -> bool {
+        let end = bounds.floor(self.position);
+        match bounds.prev(end) {
+            Some(start) => {
+                string.delete(start..end);
+                self.position = start;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Delete the grapheme after the cursor, as with the delete key, returning `false` if the
+    /// cursor was at the end of the text.
+    pub fn delete_forward(
+        &mut self,
+        string: &mut AttributedString,
+        bounds: &GraphemeBoundaries,
+    ) -> bool {
+        let start = bounds.floor(self.position);
+        match bounds.next(start) {
+            Some(end) => {
+                string.delete(start..end);
+                self.position = start;
+                true
+            }
+            None => false,
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -80,4 +307,40 @@ mod test {
         let roundtripped_string = unsafe { from_toolkit_string(toolkit_string.into_raw()) };
         assert_eq!(initial_string, &roundtripped_string);
     }
+
+    /// Boundaries of "ae\u{301}x", where "e\u{301}" is a single grapheme.
+    fn accented() -> GraphemeBoundaries {
+        GraphemeBoundaries {
+            bounds: vec![0, 1, 4, 5],
+        }
+    }
+
+    #[test]
+    fn grapheme_byte_conversions() {
+        let bounds = accented();
+        assert_eq!(bounds.num_graphemes(), 3);
+        assert_eq!(bounds.byte_to_grapheme(2), 1);
+        assert_eq!(bounds.byte_to_grapheme(5), 3);
+        assert_eq!(bounds.grapheme_to_byte(2), 4);
+        assert_eq!(bounds.grapheme_to_byte(7), 5);
+        assert_eq!(bounds.grapheme_range(1), Some(1..4));
+        assert_eq!(bounds.grapheme_range(3), None);
+        assert!(!bounds.is_boundary(2));
+    }
+
+    #[test]
+    fn cursor_movement_skips_whole_graphemes() {
+        let bounds = accented();
+        let mut cursor = GraphemeCursor::new();
+        assert!(!cursor.move_prev(&bounds));
+        assert!(cursor.move_next(&bounds));
+        assert!(cursor.move_next(&bounds));
+        assert_eq!(cursor.position(), 4);
+        cursor.set_position(&bounds, 3);
+        assert_eq!(cursor.position(), 1);
+        cursor.move_to_end(&bounds);
+        assert!(!cursor.move_next(&bounds));
+        assert!(cursor.move_prev(&bounds));
+        assert_eq!(cursor.grapheme(&bounds), 2);
+    }
 }
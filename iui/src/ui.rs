@@ -1,13 +1,21 @@
-use callback_helpers::{from_void_ptr, to_heap_ptr};
+use callback_helpers::{
+    self, from_void_ptr, register_callback, register_transient, release_transient,
+};
 use error::UIError;
 use ffi_tools;
 use std::os::raw::{c_int, c_void};
 use ui_sys;
 
+use std::any::Any;
+use std::cell::RefCell;
+use std::collections::{HashMap, VecDeque};
 use std::ffi::CStr;
 use std::marker::PhantomData;
 use std::mem;
-use std::rc::Rc;
+use std::ptr;
+use std::rc::{Rc, Weak};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
 use std::thread;
 use std::time::{Duration, SystemTime};
 
@@ -17,6 +25,47 @@ use controls::Window;
 struct UIToken {
     // This PhantomData prevents UIToken from being Send and Sync
     _pd: PhantomData<*mut ()>,
+    // State shared with every `UIHandle`.
+    shared: Arc<Mutex<Shared>>,
+    // The receivers of every channel, by ID, each a `Receiver<T>`.
+    receivers: RefCell<HashMap<usize, Rc<dyn Any>>>,
+}
+
+/// The state of the UI which other threads can reach through a `UIHandle`.
+struct Shared {
+    // Whether libUI is still initialized.
+    alive: bool,
+    // Functions queued through a `UIHandle` which have not run yet, by ID.
+    pending: HashMap<usize, Box<dyn FnOnce(&UI) + Send>>,
+}
+
+impl Shared {
+    fn new() -> Shared {
+        Shared {
+            alive: true,
+            pending: HashMap::new(),
+        }
+    }
+}
+
+thread_local! {
+    // The running UI, through which functions queued from other threads reach it.
+    static CURRENT_UI: RefCell<Weak<UIToken>> = RefCell::new(Weak::new());
+}
+
+/// The receiving half of a channel, which stays on the GUI thread.
+struct Receiver<T> {
+    callback: RefCell<Box<dyn FnMut(&UI, T)>>,
+    // Values which have arrived but not yet been passed to the callback, such as those which
+    // arrive from a nested event loop while the callback is running.
+    queued: RefCell<VecDeque<T>>,
+}
+
+/// Returns an ID for a queued function or channel, unique for the lifetime of the process so
+/// that functions queued for one UI can never be mistaken for those of another.
+fn next_id() -> usize {
+    static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+    NEXT_ID.fetch_add(1, Ordering::Relaxed)
 }
 
 impl Drop for UIToken {
@@ -25,11 +74,18 @@ impl Drop for UIToken {
             ffi_tools::is_initialized(),
             "Attempted to uninit libUI in UIToken destructor when libUI was not initialized!"
         );
+        // Once this is cleared, no handle can queue another callback, so none can reach libUI
+        // after it is uninitialized. The functions still queued are dropped here, outside of
+        // the lock, as dropping them may drop a `UISender` which uses it.
+        drop(shut_down(&self.shared));
         unsafe {
             Window::destroy_all_windows();
             ui_sys::uiUninit();
             ffi_tools::unset_initialized();
+            callback_helpers::release_all();
         }
+        // Channel receivers are callbacks too, and are dropped along with the others.
+        self.receivers.get_mut().clear();
     }
 }
 
@@ -50,6 +106,9 @@ impl UI {
     /// the UI, so do _not_ spin off your UI interactions into an alternative thread. You're likely to
     /// have problems on Mac OS.
     ///
+    /// The calling thread is recorded, and controls and callbacks used from any other thread
+    /// panic in debug builds, or in all builds with the `enforce-main-thread` feature enabled.
+    ///
     /// ```
     /// # use iui::UI;
     /// {
@@ -82,9 +141,13 @@ impl UI {
             if err.is_null() {
                 // Success! We can safely give the user a token allowing them to do UI things.
                 ffi_tools::set_initialized();
-                Ok(UI {
-                    token: Rc::new(UIToken { _pd: PhantomData }),
-                })
+                let token = Rc::new(UIToken {
+                    _pd: PhantomData,
+                    shared: Arc::new(Mutex::new(Shared::new())),
+                    receivers: RefCell::new(HashMap::new()),
+                });
+                CURRENT_UI.with(|current| *current.borrow_mut() = Rc::downgrade(&token));
+                Ok(UI { token })
             } else {
                 // Error occurred; copy the string describing it, then free that memory.
                 let error_string = CStr::from_ptr(err).to_string_lossy().into_owned();
@@ -138,27 +201,313 @@ impl UI {
         extern "C" fn c_callback<G: FnMut()>(data: *mut c_void) {
             unsafe {
                 from_void_ptr::<G>(data)();
+                release_transient(data);
             }
         }
 
         unsafe {
-            ui_sys::uiQueueMain(Some(c_callback::<F>), to_heap_ptr(callback));
+            ui_sys::uiQueueMain(Some(c_callback::<F>), register_transient(callback));
         }
     }
 
-    /// Set a callback to be run when the application quits.
-    pub fn on_should_quit<F: FnMut() + 'static>(&self, callback: F) {
-        extern "C" fn c_callback<G: FnMut()>(data: *mut c_void) -> i32 {
+    /// Returns a handle which other threads can use to queue functions to be executed on the
+    /// GUI thread, for as long as the UI is running.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use iui::prelude::*;
+    /// use std::thread;
+    ///
+    /// let ui = UI::init().unwrap();
+    ///
+    /// let handle = ui.handle();
+    /// thread::spawn(move || {
+    ///     let answer = 6 * 7;
+    ///     handle
+    ///         .queue_main(move |ui| {
+    ///             println!("The answer is {}", answer);
+    ///             ui.quit();
+    ///         })
+    ///         .expect("the UI has shut down");
+    /// });
+    /// ui.main();
+    /// ```
+    pub fn handle(&self) -> UIHandle {
+        UIHandle {
+            shared: self.token.shared.clone(),
+        }
+    }
+
+    /// Creates a channel through which other threads can send values to the GUI thread, where
+    /// each is passed to `receiver`, in the order they were sent. Returns the sending half,
+    /// which is `Send` and `Sync`, so that it can be passed to other threads.
+    ///
+    /// Unlike functions queued through a [`UIHandle`](struct.UIHandle.html), the receiver stays
+    /// on the GUI thread, so it may hold controls. Values which arrive while the receiver is
+    /// already running, such as while it shows a modal dialog, are passed to it once it
+    /// returns. It is dropped once every `UISender` has been dropped and the values already
+    /// sent have been received, or when the UI is dropped.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use iui::controls::ProgressBar;
+    /// use iui::prelude::*;
+    /// use std::thread;
+    /// use std::time::Duration;
+    ///
+    /// let ui = UI::init().unwrap();
+    /// let mut win = Window::new(&ui, "Working", 200, 50, WindowType::NoMenubar);
+    /// let progress = ProgressBar::new();
+    /// win.set_child(&ui, progress.clone());
+    /// win.show(&ui);
+    ///
+    /// let mut bar = progress.clone();
+    /// let sender = ui.channel(move |ui, percent: u32| bar.set_value(ui, percent));
+    /// thread::spawn(move || {
+    ///     for percent in 0..=100 {
+    ///         thread::sleep(Duration::from_millis(50));
+    ///         if sender.send(percent).is_err() {
+    ///             // The UI has shut down.
+    ///             break;
+    ///         }
+    ///     }
+    /// });
+    /// ui.main();
+    /// ```
+    pub fn channel<T, F>(&self, receiver: F) -> UISender<T>
+    where
+        T: Send + 'static,
+        F: FnMut(&UI, T) + 'static,
+    {
+        ffi_tools::assert_main_thread();
+        let id = next_id();
+        let receiver = Receiver {
+            callback: RefCell::new(Box::new(receiver) as Box<dyn FnMut(&UI, T)>),
+            queued: RefCell::new(VecDeque::new()),
+        };
+        self.token
+            .receivers
+            .borrow_mut()
+            .insert(id, Rc::new(receiver));
+        UISender {
+            channel: Arc::new(Channel {
+                id,
+                handle: self.handle(),
+            }),
+            _pd: PhantomData,
+        }
+    }
+
+    /// Passes a value sent through a channel to its receiver.
+    fn receive<T: 'static>(&self, id: usize, value: T) {
+        // The receiver is cloned out of the map, so that while it runs, it may create channels
+        // and its own channel may be closed.
+        let receiver = match self.token.receivers.borrow().get(&id) {
+            Some(receiver) => receiver.clone(),
+            None => return,
+        };
+        let receiver = match receiver.downcast::<Receiver<T>>() {
+            Ok(receiver) => receiver,
+            Err(_) => return,
+        };
+        receiver.queued.borrow_mut().push_back(value);
+        // If the callback is already running further up the stack, it receives the value once
+        // it returns there.
+        let mut callback = match receiver.callback.try_borrow_mut() {
+            Ok(callback) => callback,
+            Err(_) => return,
+        };
+        loop {
+            let value = match receiver.queued.borrow_mut().pop_front() {
+                Some(value) => value,
+                None => break,
+            };
+            (*callback)(self, value);
+        }
+    }
+
+    /// Runs a function on the GUI thread repeatedly, waiting the given interval before each
+    /// call, for as long as it returns `true`. Once it returns `false`, the timer stops and the
+    /// function is dropped. Returns immediately.
+    ///
+    /// The interval is rounded down to whole milliseconds. Calls may be delayed while the GUI
+    /// thread is busy, but never run early.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use iui::prelude::*;
+    /// use std::time::Duration;
+    ///
+    /// let ui = UI::init().unwrap();
+    ///
+    /// let mut ticks = 0;
+    /// ui.timer(Duration::from_secs(1), move || {
+    ///     ticks += 1;
+    ///     println!("Tick {}", ticks);
+    ///     ticks < 10
+    /// });
+    /// ui.main();
+    /// ```
+    pub fn timer<F: FnMut() -> bool + 'static>(&self, interval: Duration, callback: F) {
+        extern "C" fn c_callback<G: FnMut() -> bool>(data: *mut c_void) -> c_int {
             unsafe {
-                from_void_ptr::<G>(data)();
-                0
+                let keep_running = from_void_ptr::<G>(data)();
+                if !keep_running {
+                    // The UI library will not call this timer again.
+                    release_transient(data);
+                }
+                keep_running as c_int
             }
         }
 
+        let millis = interval.as_secs() as u128 * 1000 + interval.subsec_millis() as u128;
+        let millis = if millis > c_int::max_value() as u128 {
+            c_int::max_value()
+        } else {
+            millis as c_int
+        };
         unsafe {
-            ui_sys::uiOnShouldQuit(Some(c_callback::<F>), to_heap_ptr(callback));
+            ui_sys::uiTimer(millis, Some(c_callback::<F>), register_transient(callback));
         }
     }
+
+    /// Set a callback to be run when the user asks the application to quit, such as through
+    /// the menu item added by `Menu::append_quit_item` or the platform's own quit command.
+    ///
+    /// If the callback returns `true`, the application quits as if `quit` had been called;
+    /// if it returns `false`, the request is ignored. Until a callback is set, requests to
+    /// quit are ignored.
+    pub fn on_should_quit<F: FnMut() -> bool + 'static>(&self, callback: F) {
+        extern "C" fn c_callback<G: FnMut() -> bool>(data: *mut c_void) -> i32 {
+            unsafe { from_void_ptr::<G>(data)() as i32 }
+        }
+
+        unsafe {
+            ui_sys::uiOnShouldQuit(
+                Some(c_callback::<F>),
+                register_callback(ptr::null_mut(), "on_should_quit", callback),
+            );
+        }
+    }
+}
+
+/// A handle through which any thread can queue functions to be executed on the GUI thread.
+///
+/// Unlike [`UI`](struct.UI.html), this is `Send` and `Sync`, so it can be cloned and passed to
+/// worker threads, which use it to deliver their results to the GUI thread. Get one with
+/// [`UI::handle`](struct.UI.html#method.handle).
+///
+/// Queued functions must be `Send`, so they cannot hold controls; to update controls from
+/// another thread, send values to a receiver created with
+/// [`UI::channel`](struct.UI.html#method.channel) instead.
+#[derive(Clone)]
+pub struct UIHandle {
+    shared: Arc<Mutex<Shared>>,
+}
+
+impl UIHandle {
+    /// Queues a function to be executed on the GUI thread when next possible, where it is
+    /// passed the `UI`. Returns immediately, not waiting for the function to be executed.
+    ///
+    /// If the UI has already shut down, this returns a
+    /// [`ShutDownError`](enum.UIError.html#variant.ShutDownError) instead. Functions which are
+    /// still queued when the UI shuts down are dropped without being executed.
+    pub fn queue_main<F: FnOnce(&UI) + Send + 'static>(&self, callback: F) -> Result<(), UIError> {
+        extern "C" fn c_callback(data: *mut c_void) {
+            ffi_tools::assert_main_thread();
+            let token = match CURRENT_UI.with(|current| current.borrow().upgrade()) {
+                Some(token) => token,
+                None => return,
+            };
+            let callback = lock_shared(&token.shared).pending.remove(&(data as usize));
+            if let Some(callback) = callback {
+                callback(&UI { token });
+            }
+        }
+
+        // The lock is held while queueing, so that the UI cannot shut down in the meantime.
+        let mut shared = lock_shared(&self.shared);
+        if !shared.alive {
+            return Err(UIError::ShutDownError());
+        }
+        let id = next_id();
+        shared.pending.insert(id, Box::new(callback));
+        unsafe {
+            ui_sys::uiQueueMain(Some(c_callback), id as *mut c_void);
+        }
+        Ok(())
+    }
+
+    /// Returns `true` if the UI is still running, so that functions can be queued on it.
+    pub fn is_alive(&self) -> bool {
+        lock_shared(&self.shared).alive
+    }
+}
+
+/// The sending half of a channel created with [`UI::channel`](struct.UI.html#method.channel),
+/// which any thread can use to send values to its receiver on the GUI thread.
+pub struct UISender<T> {
+    channel: Arc<Channel>,
+    _pd: PhantomData<fn(T)>,
+}
+
+impl<T> Clone for UISender<T> {
+    fn clone(&self) -> UISender<T> {
+        UISender {
+            channel: self.channel.clone(),
+            _pd: PhantomData,
+        }
+    }
+}
+
+impl<T: Send + 'static> UISender<T> {
+    /// Sends a value to the receiver, which is passed it on the GUI thread when next possible.
+    /// Returns immediately, not waiting for the value to be received.
+    ///
+    /// If the UI has already shut down, this returns a
+    /// [`ShutDownError`](enum.UIError.html#variant.ShutDownError) instead.
+    pub fn send(&self, value: T) -> Result<(), UIError> {
+        let id = self.channel.id;
+        self.channel
+            .handle
+            .queue_main(move |ui| ui.receive(id, value))
+    }
+}
+
+/// A channel shared by all of its senders, which drops its receiver once they are all gone.
+struct Channel {
+    id: usize,
+    handle: UIHandle,
+}
+
+impl Drop for Channel {
+    fn drop(&mut self) {
+        let id = self.id;
+        // This is queued behind every value already sent, so they are all received first. If
+        // the UI has shut down, the receiver has already been dropped along with it.
+        let _ = self.handle.queue_main(move |ui| {
+            ui.token.receivers.borrow_mut().remove(&id);
+        });
+    }
+}
+
+/// Lock the state shared with the UI. Nothing which could panic runs while it is locked, so
+/// it remains valid even if a thread panicked while holding the lock.
+fn lock_shared(shared: &Mutex<Shared>) -> MutexGuard<'_, Shared> {
+    shared
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// Mark the UI as shut down, so that nothing more can be queued, returning the functions
+/// which were queued but have not run.
+fn shut_down(shared: &Mutex<Shared>) -> HashMap<usize, Box<dyn FnOnce(&UI) + Send>> {
+    let mut shared = lock_shared(shared);
+    shared.alive = false;
+    mem::replace(&mut shared.pending, HashMap::new())
 }
 
 /// Provides fine-grained control over the user interface event loop, exposing the `on_tick` event
@@ -246,3 +595,76 @@ impl<'s> EventLoop<'s> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn handles_refuse_work_after_shutdown() {
+        let shared = Arc::new(Mutex::new(Shared::new()));
+        let handle = UIHandle {
+            shared: shared.clone(),
+        };
+        let sent = handle.clone();
+        assert!(thread::spawn(move || sent.is_alive()).join().unwrap());
+
+        shut_down(&shared);
+        assert!(!handle.is_alive());
+        match handle.queue_main(|_| panic!("queued after shutdown")) {
+            Err(UIError::ShutDownError()) => {}
+            _ => panic!("expected a ShutDownError"),
+        }
+    }
+
+    #[test]
+    fn values_arriving_while_receiving_are_kept() {
+        let ui = UI {
+            token: Rc::new(UIToken {
+                _pd: PhantomData,
+                shared: Arc::new(Mutex::new(Shared::new())),
+                receivers: RefCell::new(HashMap::new()),
+            }),
+        };
+        let received = Rc::new(RefCell::new(Vec::new()));
+        let id = next_id();
+        let log = received.clone();
+        let receiver = Receiver {
+            callback: RefCell::new(Box::new(move |ui: &UI, value: u32| {
+                log.borrow_mut().push(value);
+                // As if a nested event loop delivered the next value.
+                if value == 1 {
+                    ui.receive(id, 2u32);
+                    assert_eq!(*log.borrow(), vec![1]);
+                }
+            }) as Box<dyn FnMut(&UI, u32)>),
+            queued: RefCell::new(VecDeque::new()),
+        };
+        ui.token
+            .receivers
+            .borrow_mut()
+            .insert(id, Rc::new(receiver));
+
+        ui.receive(id, 1u32);
+        ui.receive(id, 3u32);
+        assert_eq!(*received.borrow(), vec![1, 2, 3]);
+
+        // Dropping the UI would uninitialize the library.
+        ui.token.receivers.borrow_mut().clear();
+        mem::forget(ui);
+    }
+
+    #[test]
+    fn queued_functions_are_dropped_at_shutdown() {
+        let shared = Arc::new(Mutex::new(Shared::new()));
+        let captured = Arc::new(());
+        let held = captured.clone();
+        lock_shared(&shared)
+            .pending
+            .insert(next_id(), Box::new(move |_: &UI| drop(held)));
+
+        drop(shut_down(&shared));
+        assert_eq!(Arc::strong_count(&captured), 1);
+        assert!(lock_shared(&shared).pending.is_empty());
+    }
+}